@@ -0,0 +1,75 @@
+//! Compares casting the walls of a 1920 column frame one column after another with casting
+//! them in parallel. Run with `cargo bench --bench cast_walls`.
+
+use rust_ray::rendering::{cast_wall, cast_walls_into, Camera};
+use rust_ray::Map;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const SCREEN_WIDTH: usize = 1920;
+const SCREEN_HEIGHT: i32 = 1080;
+const FRAMES: u32 = 200;
+
+/// A 64x64 map with scattered pillars, so rays travel far and hit many kinds of faces.
+fn pillar_hall() -> Map {
+    let size = 64;
+    let data = (0..size * size)
+        .map(|i| {
+            let (x, y) = (i % size, i / size);
+            let border = x == 0 || y == 0 || x == size - 1 || y == size - 1;
+            u8::from(border || (x % 7 == 3 && y % 5 == 2))
+        })
+        .collect();
+    Map::new(size, size, data)
+}
+
+/// The camera of a frame, turning on the spot in the middle of the map.
+fn camera(map: &Map, frame: u32) -> Camera {
+    let (x, y) = map.tile_to_world(32.5, 32.5);
+    Camera::new(x, y, frame as f32 * 0.05)
+}
+
+/// Runs a casting function for every frame and returns the time per frame.
+fn bench(map: &Map, mut cast: impl FnMut(&Camera)) -> Duration {
+    // Warm up the caches and the rayon thread pool
+    for frame in 0..10 {
+        cast(&camera(map, frame));
+    }
+
+    let start = Instant::now();
+    for frame in 0..FRAMES {
+        cast(&camera(map, frame));
+    }
+    start.elapsed() / FRAMES
+}
+
+fn main() {
+    let map = pillar_hall();
+    let mut walls = vec![None; SCREEN_WIDTH];
+
+    let serial = bench(&map, |camera| {
+        for (column, wall) in walls.iter_mut().enumerate() {
+            *wall = cast_wall(&map, camera, column, SCREEN_WIDTH, SCREEN_HEIGHT);
+        }
+        black_box(&walls);
+    });
+    let parallel = bench(&map, |camera| {
+        cast_walls_into(&map, camera, SCREEN_HEIGHT, &mut walls);
+        black_box(&walls);
+    });
+
+    println!("cast_walls at {} columns:", SCREEN_WIDTH);
+    println!(
+        "  serial:   {:>8.1} µs per frame",
+        serial.as_secs_f64() * 1e6
+    );
+    println!(
+        "  parallel: {:>8.1} µs per frame",
+        parallel.as_secs_f64() * 1e6
+    );
+    println!(
+        "  speedup:  {:>8.2}x on {} threads",
+        serial.as_secs_f64() / parallel.as_secs_f64(),
+        rayon::current_num_threads()
+    );
+}
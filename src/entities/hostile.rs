@@ -1,14 +1,17 @@
 extern crate sdl2;
 
-use crate::rrm::MAP_CUBE_SIZE as SIZE;
-use crate::utilities::opengl::setup_viewport;
+use crate::rendering::draw_markers;
 use dashmap::*;
-use glu_sys::*;
 use rayon::iter::ParallelBridge;
 use rayon::iter::ParallelIterator;
+use sdl2::pixels::Color;
 use std::f32::consts::PI;
 use std::*;
 
+// Constants
+const MARKER_COLOR: Color = Color::RGB(255, 0, 0);
+const MARKER_SIZE: f32 = 16.0;
+
 pub struct HostileEntities<'a> {
     pub list: DashMap<&'a str, &'a mut HostileEntity>,
 }
@@ -32,10 +35,19 @@ impl<'a> HostileEntities<'a> {
     }
 
     pub fn draw_all(&mut self, screen_width: i32, screen_height: i32) {
-        self.list.iter_mut().par_bridge().for_each(|mut pair| {
-            let entity = pair.value_mut();
-            entity.draw_hostile_entity(screen_width, screen_height);
-        });
+        // Every entity is drawn in one batch, on the thread that owns the OpenGL context
+        let points = self
+            .list
+            .iter()
+            .map(|pair| (pair.value().x, pair.value().y))
+            .collect::<Vec<_>>();
+        draw_markers(
+            &points,
+            MARKER_SIZE,
+            MARKER_COLOR,
+            screen_width,
+            screen_height,
+        );
     }
 
     pub fn print(&self) {
@@ -43,6 +55,12 @@ impl<'a> HostileEntities<'a> {
     }
 }
 
+impl Default for HostileEntities<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(Debug)]
 pub struct HostileEntity {
     pub x: f32,
@@ -86,27 +104,26 @@ impl hash::Hash for HostileEntity {
 }
 
 impl HostileEntity {
-    pub fn new(screen_width: i32, screen_height: i32) -> Result<Self, String> {
-        unsafe {
-            // Create the x and y values directly
-            let x = screen_width as f32 / SIZE * 4.0;
-            let y = screen_height as f32 / SIZE * 4.0;
-            let angle: f32 = PI / 3.0;
-            let speed: f32 = 0.5;
-            let health: f32 = 20.0;
-            let is_alive: bool = true;
-            let sprite_id: u32 = 0;
-
-            Ok(Self {
-                x,
-                y,
-                angle,
-                speed,
-                health,
-                is_alive,
-                sprite_id,
-            })
-        }
+    /// Creates a new entity at the given world position.
+    ///
+    /// Use [`Map::player_start`](crate::rrm::Map::player_start) or
+    /// [`Map::tile_to_world`](crate::rrm::Map::tile_to_world) to place it relative to the map.
+    pub fn new(x: f32, y: f32) -> Result<Self, String> {
+        let angle: f32 = PI / 3.0;
+        let speed: f32 = 0.5;
+        let health: f32 = 20.0;
+        let is_alive: bool = true;
+        let sprite_id: u32 = 0;
+
+        Ok(Self {
+            x,
+            y,
+            angle,
+            speed,
+            health,
+            is_alive,
+            sprite_id,
+        })
     }
 
     fn follow_player(&mut self, player_x: f32, player_y: f32) {
@@ -114,8 +131,6 @@ impl HostileEntity {
         let dx = player_x - self.x;
         let dy = player_y - self.y;
 
-        self.speed = 0.5;
-
         // Calculate distance to player
         let distance = (dx * dx + dy * dy).sqrt();
 
@@ -131,24 +146,4 @@ impl HostileEntity {
             self.y += direction_y * self.speed;
         }
     }
-
-    fn draw_hostile_entity(&mut self, screen_width: i32, screen_height: i32) {
-        let entity_x = self.x as i32;
-        let entity_y = self.y as i32;
-
-        unsafe {
-            setup_viewport(screen_width, screen_height);
-
-            glColor3f(1.0, 0.0, 0.0);
-            glPointSize(16.0);
-            glBegin(GL_POINTS);
-            glVertex2i(entity_x, entity_y);
-            glEnd();
-
-            glLineWidth(3.00);
-            glBegin(GL_LINES);
-            glVertex2i(entity_x, entity_y);
-            glEnd();
-        }
-    }
 }
@@ -0,0 +1,109 @@
+use super::hostile::{HostileEntities, HostileEntity};
+use super::neutral::{NeutralEntities, NeutralEntity};
+use crate::rendering::Sprite;
+use crate::rrm::spawn::EntityKind;
+use crate::rrm::Map;
+
+/// The entities placed in a map, created from its `ENTITY=` headers.
+///
+/// The entity collections only borrow their entities, so `LevelEntities` owns them
+/// and hands out ready-made collections with [`LevelEntities::collections`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use rust_ray::entities::LevelEntities;
+/// use rust_ray::load_map;
+///
+/// # fn main() -> Result<(), rust_ray::RrmError> {
+/// let map = load_map("level_1")?;
+/// let mut level_entities = LevelEntities::from_map(&map);
+/// let (mut hostile, mut neutral) = level_entities.collections();
+///
+/// hostile.update_all(100.0, 100.0);
+/// neutral.update_all(100.0, 100.0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct LevelEntities {
+    /// The hostile entities with their names.
+    pub hostile: Vec<(String, HostileEntity)>,
+    /// The neutral entities with their names.
+    pub neutral: Vec<(String, NeutralEntity)>,
+}
+
+impl LevelEntities {
+    /// Creates the entities placed in a map, converting their positions to world units.
+    pub fn from_map(map: &Map) -> Self {
+        let mut level_entities = Self::default();
+
+        for spawn in map.entity_spawns() {
+            let (x, y) = map.tile_to_world(spawn.x, spawn.y);
+            let angle = spawn.angle.to_radians();
+
+            match spawn.kind {
+                EntityKind::Hostile => level_entities.hostile.push((
+                    spawn.name.clone(),
+                    HostileEntity {
+                        x,
+                        y,
+                        angle,
+                        speed: spawn.speed,
+                        health: spawn.health,
+                        is_alive: true,
+                        sprite_id: spawn.sprite_id,
+                    },
+                )),
+                EntityKind::Neutral => level_entities.neutral.push((
+                    spawn.name.clone(),
+                    NeutralEntity {
+                        x,
+                        y,
+                        angle,
+                        speed: spawn.speed,
+                        health: spawn.health,
+                        is_alive: true,
+                        sprite_id: spawn.sprite_id,
+                    },
+                )),
+            }
+        }
+
+        level_entities
+    }
+
+    /// Returns the billboard sprites of all living entities, for drawing them in the 3D view.
+    pub fn sprites(&self) -> Vec<Sprite> {
+        let hostile = self
+            .hostile
+            .iter()
+            .filter(|(_, entity)| entity.is_alive)
+            .map(|(_, entity)| (entity.x, entity.y, entity.sprite_id));
+        let neutral = self
+            .neutral
+            .iter()
+            .filter(|(_, entity)| entity.is_alive)
+            .map(|(_, entity)| (entity.x, entity.y, entity.sprite_id));
+
+        hostile
+            .chain(neutral)
+            .map(|(x, y, sprite_id)| Sprite { x, y, sprite_id })
+            .collect()
+    }
+
+    /// Returns the hostile and neutral entity collections of the level, keyed by entity name.
+    pub fn collections(&mut self) -> (HostileEntities<'_>, NeutralEntities<'_>) {
+        let mut hostile = HostileEntities::new();
+        for (name, entity) in self.hostile.iter_mut() {
+            hostile.add(name, entity);
+        }
+
+        let mut neutral = NeutralEntities::new();
+        for (name, entity) in self.neutral.iter_mut() {
+            neutral.add(name, entity);
+        }
+
+        (hostile, neutral)
+    }
+}
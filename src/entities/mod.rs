@@ -1,5 +1,7 @@
 mod hostile;
+mod level;
 mod neutral;
 
 pub use hostile::{HostileEntities, HostileEntity};
+pub use level::LevelEntities;
 pub use neutral::{NeutralEntities, NeutralEntity};
@@ -12,18 +12,31 @@
 //!
 //! Here's a minimal example:
 //!
-//! ```rust
+//! ```rust,no_run
 //! use rust_ray::{SdlWindow, map_initialize, check_gl_error};
+//! use rust_ray::rendering::{render_3d, Camera, Textures};
 //! use sdl2::event::Event;
 //!
+//! # struct GameState;
+//! # impl GameState {
+//! #     fn update(&mut self, _event: &Event) -> bool {
+//! #         false
+//! #     }
+//! # }
+//! #
 //! // This is the function for anything that is game specific
 //! fn run_game() -> Result<(), String> {
 //!     // 1. Initialize window and game state
-//!     let window = SdlWindow::new("My Game", 1280, 720)?;
+//!     let mut window = SdlWindow::new("My Game", 1280, 720)?;
+//!     let mut game_state = GameState;
 //!     
-//!     // 2. Set up initial game state
-//!     let player_x = 1280.0 / 4.0;
-//!     let player_y = 720.0 / 4.0;
+//!     // 2. Load the map and set up initial game state
+//!     let map = map_initialize("./assets/maps").map_err(|e| e.to_string())?;
+//!     let (player_x, player_y, player_angle) = map
+//!         .player_start()
+//!         .unwrap_or((1.5 * map.cube_size(), 1.5 * map.cube_size(), 0.0));
+//!     let camera = Camera::new(player_x, player_y, player_angle);
+//!     let textures = Textures::load(&map)?;
 //!
 //!     // 3. Run game loop
 //!     // The engine doesnt implement any controls, you have to do that yourself!
@@ -41,10 +54,8 @@
 //!             }
 //!         }
 //!
-//!         // Initialize map
-//!         map_initialize("./assets/maps")?;
-//!
 //!         // Render current frame
+//!         render_3d(&map, &textures, &[], &camera, 1280, 720);
 //!         window.swap_window();
 //!         check_gl_error();
 //!     }
@@ -101,17 +112,30 @@
 //! The engine is organized into several main modules:
 //!
 //! - [`rendering`]: Handles all graphics rendering operations
+//!   - `camera`: The point of view the scenes are rendered from
+//!   - `depth_buffer`: Per-column wall distances of the 3D view
+//!   - `image`: RGBA images used for textures and frames
 //!   - `map`: Map rendering functionality
 //!   - `player`: Player rendering systems
+//!   - `raycast`: Ray-hit queries for the renderer and gameplay code
 //!   - `renderer`: Core 2D and 3D rendering systems
+//!   - `gpu`: Batched OpenGL 3.3 passes behind the renderer, one draw call per pass
+//!   - `software`: CPU rasterizer for headless rendering into images
+//!   - `sprite`: Billboard sprites and sprite sheets
+//!   - `texture`: Wall and floor textures loaded from the map palette
 //!
 //! - [`rrm`]: Custom Rust Ray Map (RRM) format support
+//!   - `error`: The [`RrmError`] type describing why a map couldn't be loaded
+//!   - `map`: The [`Map`] type holding a loaded map
 //!   - `rrm_support`: Handles loading and parsing of .rrm map files
+//!   - `rrm_writer`: Writes maps back out as .rrm files
 //!
 //! - [`utilities`]: Common utility functions and helpers
 //!   - `opengl`: OpenGL utility functions
+//!   - `png`: PNG encoding and decoding for screenshots and reference images
 //!
 //! - [`window`]: Window management and creation
+//!   - `recorder`: Saves a range of frames as a numbered PNG sequence
 //!   - `sdl_window`: SDL2 window implementation and OpenGL context management
 
 pub mod entities;
@@ -120,6 +144,7 @@ pub mod rrm;
 pub mod utilities;
 pub mod window;
 
-pub use rrm::rrm_support::{map_initialize, MAP_CUBE_SIZE, MAP_DATA, MAP_HEIGHT, MAP_WIDTH};
-pub use utilities::opengl::{clear_screen, setup_viewport};
+pub use rrm::{list_maps, load_map, map_initialize, Map, RrmError};
+pub use utilities::opengl::{clear_screen, draw_image, draw_image_scaled, setup_viewport};
+pub use window::recorder::FrameRecorder;
 pub use window::sdl_window::{check_gl_error, SdlWindow};
@@ -0,0 +1,150 @@
+//! # Camera Module
+//!
+//! This module provides the [`Camera`] the 2D and 3D views are rendered from.
+//!
+//! The camera holds everything about the point of view: where it is, which way it faces,
+//! how wide the field of view is, how far it looks up or down and how close sprites may get
+//! before they are clipped. Games can change these at any time, e.g. for a FOV slider, zoom
+//! effects or looking up and down.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rust_ray::rendering::Camera;
+//! use rust_ray::Map;
+//!
+//! let map = Map::default();
+//! let (x, y, angle) = map.player_start().unwrap_or((96.0, 96.0, 0.0));
+//!
+//! let mut camera = Camera::new(x, y, angle);
+//! camera.fov = 90f32.to_radians();
+//! camera.pitch = 0.1; // Look up a bit
+//! ```
+
+use std::f32::consts::PI;
+use std::*;
+
+/// The field of view of a new camera, 60 degrees.
+pub const DEFAULT_FOV: f32 = PI / 3.0;
+
+/// The near clip distance of a new camera in world units.
+pub const DEFAULT_NEAR_CLIP: f32 = 1.0;
+
+// Constants
+const MIN_FOV: f32 = 0.01; // Narrower fields of view are rendered with this one, in radians
+const MAX_FOV: f32 = PI - 0.01; // Wider fields of view are rendered with this one, in radians
+const MIN_NEAR_CLIP: f32 = 0.001; // Smaller near clip distances are rendered with this one
+
+/// The point of view the 2D and 3D views are rendered from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    /// The x-coordinate in world units.
+    pub x: f32,
+    /// The y-coordinate in world units.
+    pub y: f32,
+    /// The viewing angle in radians, 0 faces along the x-axis.
+    pub angle: f32,
+    /// The horizontal field of view in radians, greater than 0 and less than PI.
+    /// Values outside of that range are rendered with the closest valid field of view.
+    pub fov: f32,
+    /// Moves the horizon by this fraction of the screen height, positive values look up.
+    pub pitch: f32,
+    /// Sprites closer than this distance in world units are clipped. Closer walls are still
+    /// drawn, but no higher than a wall at this distance. The distance has to be greater
+    /// than 0, smaller values are rendered with a small positive distance.
+    pub near_clip: f32,
+}
+
+impl Camera {
+    /// Creates a camera with the default field of view, no pitch and the default near clip.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The x-coordinate in world units.
+    /// * `y` - The y-coordinate in world units.
+    /// * `angle` - The viewing angle in radians.
+    pub fn new(x: f32, y: f32, angle: f32) -> Self {
+        Self {
+            x,
+            y,
+            angle,
+            fov: DEFAULT_FOV,
+            pitch: 0.0,
+            near_clip: DEFAULT_NEAR_CLIP,
+        }
+    }
+
+    /// Returns the position of the camera in world units.
+    pub fn position(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    /// Returns the angle of the ray shown in a screen column.
+    ///
+    /// The rays go through the centers of the columns on a camera plane in front of the
+    /// camera, which spans the field of view. Columns are therefore evenly spaced on the
+    /// screen, while the angle between neighbouring rays shrinks towards the edges.
+    ///
+    /// # Arguments
+    ///
+    /// * `column` - The screen column.
+    /// * `columns` - The number of screen columns.
+    pub fn ray_angle(&self, column: usize, columns: usize) -> f32 {
+        // Where the column lies on the camera plane, from -1 at the left edge to 1 at the right
+        let plane_x = 2.0 * (column as f32 + 0.5) / columns as f32 - 1.0;
+        self.angle + (plane_x * self.plane_half_width()).atan()
+    }
+
+    /// Projects a point in the world onto the screen columns.
+    ///
+    /// This is the inverse of [`Camera::ray_angle`], column `c` covers the range `c..c + 1`.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The x-coordinate of the point in world units.
+    /// * `y` - The y-coordinate of the point in world units.
+    /// * `columns` - The number of screen columns.
+    ///
+    /// # Returns
+    ///
+    /// The screen column the point is seen in and its perpendicular distance from the camera,
+    /// or `None` if the point isn't in front of the camera.
+    pub fn project(&self, x: f32, y: f32, columns: usize) -> Option<(f32, f32)> {
+        let (dx, dy) = (x - self.x, y - self.y);
+        let (sin, cos) = self.angle.sin_cos();
+        let distance = dx * cos + dy * sin;
+        if distance <= 0.0 {
+            return None;
+        }
+
+        let plane_x = (dy * cos - dx * sin) / distance / self.plane_half_width();
+        Some(((plane_x + 1.0) / 2.0 * columns as f32, distance))
+    }
+
+    /// Returns the near clip distance in world units, at least a small positive distance.
+    pub(crate) fn clamped_near_clip(&self) -> f32 {
+        self.near_clip.max(MIN_NEAR_CLIP)
+    }
+
+    /// Returns half the width of the camera plane one unit in front of the camera, with the
+    /// field of view clamped to the valid range.
+    fn plane_half_width(&self) -> f32 {
+        (self.fov.clamp(MIN_FOV, MAX_FOV) / 2.0).tan()
+    }
+
+    /// Returns the screen row of the horizon.
+    ///
+    /// # Arguments
+    ///
+    /// * `screen_height` - The height of the screen.
+    pub fn horizon(&self, screen_height: i32) -> f32 {
+        screen_height as f32 * (0.5 + self.pitch)
+    }
+}
+
+impl Default for Camera {
+    /// Returns a camera at the origin facing along the x-axis.
+    fn default() -> Self {
+        Self::new(0.0, 0.0, 0.0)
+    }
+}
@@ -0,0 +1,79 @@
+//! # Depth Buffer Module
+//!
+//! This module provides the [`DepthBuffer`] produced by the 3D pass. It holds the distance
+//! of the wall in every screen column, so anything drawn on top of the 3D view, e.g. sprites,
+//! particles, weapon effects or custom overlays, can be hidden behind closer walls.
+
+use std::*;
+
+/// The perpendicular distance of the wall in every screen column of a 3D frame.
+///
+/// Distances are in world units along the viewing direction, the same distance the wall
+/// height is computed from. Columns where no wall was hit are `f32::INFINITY`.
+///
+/// # Example
+///
+/// ```rust
+/// use rust_ray::rendering::DepthBuffer;
+///
+/// let depth = DepthBuffer::from(vec![64.0, f32::INFINITY]);
+///
+/// assert!(depth.is_visible(0, 32.0));
+/// assert!(!depth.is_visible(0, 128.0));
+/// assert!(depth.is_visible(1, 1000.0));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DepthBuffer {
+    distances: Vec<f32>,
+}
+
+impl DepthBuffer {
+    /// Creates a depth buffer without walls for the given number of columns.
+    pub fn new(columns: usize) -> Self {
+        Self {
+            distances: vec![f32::INFINITY; columns],
+        }
+    }
+
+    /// Returns the number of screen columns.
+    pub fn len(&self) -> usize {
+        self.distances.len()
+    }
+
+    /// Returns true if the buffer has no columns.
+    pub fn is_empty(&self) -> bool {
+        self.distances.is_empty()
+    }
+
+    /// Returns the distance of the wall in a column, or `None` if the column is off screen.
+    pub fn get(&self, column: usize) -> Option<f32> {
+        self.distances.get(column).copied()
+    }
+
+    /// Sets the distance of the wall in a column.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the column is off screen.
+    pub fn set(&mut self, column: usize, distance: f32) {
+        self.distances[column] = distance;
+    }
+
+    /// Returns whether something at the given distance is in front of the wall in a column.
+    ///
+    /// Columns that are off screen are never visible.
+    pub fn is_visible(&self, column: usize, distance: f32) -> bool {
+        self.get(column).is_some_and(|wall| distance <= wall)
+    }
+
+    /// Returns the distances of all columns from left to right.
+    pub fn as_slice(&self) -> &[f32] {
+        &self.distances
+    }
+}
+
+impl From<Vec<f32>> for DepthBuffer {
+    fn from(distances: Vec<f32>) -> Self {
+        Self { distances }
+    }
+}
@@ -0,0 +1,1046 @@
+//! # GPU Module
+//!
+//! This module provides the OpenGL 3.3 renderer behind the functions of the
+//! [`renderer`](super::renderer) module. Every pass builds one vertex buffer on the CPU and
+//! draws it with a single draw call through one small shader program: the 2D map and the
+//! player, the wall columns, the billboard sprites and the entity markers.
+//!
+//! Walls and sprites are depth tested on the GPU, sprites write their depth wherever they are
+//! opaque. The floor and the ceiling are drawn behind the walls as flat coloured quads, one for
+//! every run of screen rows with the same colour in the fog. Only maps with per-tile floors
+//! are cast on the CPU like in the [`software`](super::software) renderer and drawn as one
+//! textured quad.
+//!
+//! Lower render resolutions are rendered into an offscreen framebuffer, which is copied to the
+//! screen with the nearest pixel.
+//!
+//! The renderer is created on the first frame of every OpenGL context of an
+//! [`SdlWindow`](crate::window::SdlWindow) and deleted with the window. If that fails, e.g.
+//! because the driver doesn't support OpenGL 3.3, the fixed-function path is used instead.
+
+use super::camera::Camera;
+use super::depth_buffer::DepthBuffer;
+use super::image::Image;
+use super::map::map_rects_2d;
+use super::player::player_rects;
+use super::renderer::{cast_walls_into, WallColumn};
+use super::software::{
+    casts_floor, draw_floor_and_ceiling, flat_row_color, project_sprite, side_brightness,
+};
+use super::sprite::Sprite;
+use super::texture::Textures;
+use crate::rrm::Map;
+use crate::utilities::opengl::{clear_screen, setup_viewport};
+use gl::types::{GLchar, GLenum, GLint, GLsizei, GLsizeiptr, GLuint};
+use sdl2::pixels::Color;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::ffi::{c_void, CString};
+use std::*;
+
+// Constants
+const UNTEXTURED: f32 = -1.0; // Texture layer of vertices drawn in their colour only
+const OPAQUE: f32 = -1.0; // Alpha cutoff that keeps every pixel
+const SPRITE_ALPHA_CUTOFF: f32 = 0.5; // Sprite pixels with at most this alpha are transparent
+const UNTEXTURED_WALL_COLOR: Color = Color::RGB(0, 255, 0);
+
+const VERTEX_SHADER: &str = r#"#version 330 core
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec3 texture_coords;
+layout(location = 2) in vec3 color;
+layout(location = 3) in vec4 fog;
+
+uniform vec2 screen_size;
+
+out vec3 frag_texture_coords;
+out vec3 frag_color;
+out vec4 frag_fog;
+
+void main() {
+    // Screen pixels with the origin in the top left corner to normalized device coordinates
+    vec2 ndc = position.xy / screen_size * 2.0 - 1.0;
+    gl_Position = vec4(ndc.x, -ndc.y, position.z, 1.0);
+
+    frag_texture_coords = texture_coords;
+    frag_color = color;
+    frag_fog = fog;
+}
+"#;
+
+const FRAGMENT_SHADER: &str = r#"#version 330 core
+
+in vec3 frag_texture_coords;
+in vec3 frag_color;
+in vec4 frag_fog;
+
+uniform sampler2DArray layers;
+uniform float alpha_cutoff;
+
+out vec4 out_color;
+
+void main() {
+    // Vertices with a negative texture layer are drawn in their colour only
+    vec4 base = vec4(1.0);
+    if (frag_texture_coords.z >= 0.0) {
+        base = textureLod(layers, frag_texture_coords, 0.0);
+    }
+    if (base.a <= alpha_cutoff) {
+        discard;
+    }
+
+    out_color = vec4(mix(base.rgb * frag_color, frag_fog.rgb, frag_fog.a), 1.0);
+}
+"#;
+
+thread_local! {
+    /// The renderers of the OpenGL contexts created on this thread, keyed by context id.
+    ///
+    /// OpenGL objects belong to a context and a context to a thread, so every thread keeps
+    /// the renderers of its own contexts until they are released with [`release_context`].
+    static RENDERERS: RefCell<HashMap<u64, Result<GpuRenderer, String>>> =
+        RefCell::new(HashMap::new());
+
+    /// The id and the SDL handle of the context made current with [`bind_context`].
+    static CURRENT_CONTEXT: Cell<Option<(u64, sdl2::sys::SDL_GLContext)>> =
+        const { Cell::new(None) };
+}
+
+/// Marks an OpenGL context as current, so frames are rendered with its renderer.
+///
+/// # Arguments
+///
+/// * `id` - The id of the context, unique for the lifetime of the program.
+/// * `context` - The SDL handle of the context, which must be current on this thread.
+pub(crate) fn bind_context(id: u64, context: sdl2::sys::SDL_GLContext) {
+    CURRENT_CONTEXT.set(Some((id, context)));
+}
+
+/// Deletes the renderer of an OpenGL context before the context is destroyed.
+///
+/// # Arguments
+///
+/// * `id` - The id of the context.
+/// * `is_current` - Whether the context is current, otherwise the OpenGL objects of the
+///   renderer can't be deleted and are released with the context.
+pub(crate) fn release_context(id: u64, is_current: bool) {
+    if CURRENT_CONTEXT
+        .get()
+        .is_some_and(|(current, _)| current == id)
+    {
+        CURRENT_CONTEXT.set(None);
+    }
+
+    if let Some(Ok(renderer)) = RENDERERS.with_borrow_mut(|renderers| renderers.remove(&id)) {
+        if is_current {
+            drop(renderer);
+        } else {
+            mem::forget(renderer);
+        }
+    }
+}
+
+/// Returns the id of the current OpenGL context.
+///
+/// # Returns
+///
+/// The id, or `None` if no context was bound with [`bind_context`] or another context was
+/// made current since then.
+fn current_context() -> Option<u64> {
+    let (id, context) = CURRENT_CONTEXT.get()?;
+    (unsafe { sdl2::sys::SDL_GL_GetCurrentContext() } == context).then_some(id)
+}
+
+/// Runs a function with the renderer of the current OpenGL context.
+///
+/// The renderer is created the first time it is needed for a context. If it can't be created,
+/// the error is kept for [`error`] and `None` is returned for every frame of that context.
+///
+/// # Returns
+///
+/// The result of the function, or `None` if there is no current context or no renderer for it.
+pub(crate) fn with_renderer<R>(f: impl FnOnce(&mut GpuRenderer) -> R) -> Option<R> {
+    let id = current_context()?;
+
+    RENDERERS.with_borrow_mut(|renderers| {
+        let renderer = renderers
+            .entry(id)
+            .or_insert_with(|| unsafe { GpuRenderer::new() });
+
+        match renderer {
+            Ok(renderer) => Some(f(renderer)),
+            Err(_) => None,
+        }
+    })
+}
+
+/// Returns why the renderer couldn't be created for the current OpenGL context.
+///
+/// # Returns
+///
+/// The error, or `None` if the renderer works or hasn't been created for the context yet.
+pub(crate) fn error() -> Option<String> {
+    let id = current_context()?;
+    RENDERERS.with_borrow(|renderers| match renderers.get(&id) {
+        Some(Err(e)) => Some(e.clone()),
+        _ => None,
+    })
+}
+
+/// A vertex of the batched geometry.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct Vertex {
+    /// The position in screen pixels and the depth from -1 (near) to 1 (far).
+    position: [f32; 3],
+    /// The texture coordinates and the texture layer, `UNTEXTURED` for plain colours.
+    texture_coords: [f32; 3],
+    /// The colour the texture is multiplied with, from 0 to 1.
+    color: [f32; 3],
+    /// The fog colour and how much of it covers the vertex, from 0 to 1.
+    fog: [f32; 4],
+}
+
+/// A screen-aligned rectangle drawn as two triangles.
+#[derive(Debug, Clone, Copy)]
+struct Quad {
+    /// The left, top, right and bottom edges in screen pixels.
+    rect: [f32; 4],
+    /// The depth from -1 (near) to 1 (far), see [`depth_of`].
+    depth: f32,
+    /// The texture coordinates of the left, top, right and bottom edges.
+    texture_rect: [f32; 4],
+    /// The texture layer, `UNTEXTURED` for plain colours.
+    layer: f32,
+    /// The colour the texture is multiplied with, from 0 to 1.
+    color: [f32; 3],
+    /// The fog colour and how much of it covers the quad, from 0 to 1.
+    fog: [f32; 4],
+}
+
+impl Quad {
+    /// Creates a quad in a plain colour, in front of everything and without fog.
+    fn colored(x: f32, y: f32, width: f32, height: f32, color: Color) -> Self {
+        Self {
+            rect: [x, y, x + width, y + height],
+            depth: -1.0,
+            texture_rect: [0.0; 4],
+            layer: UNTEXTURED,
+            color: rgb(color),
+            fog: [0.0; 4],
+        }
+    }
+
+    /// Appends the two triangles of the quad to a vertex buffer.
+    fn push(&self, vertices: &mut Vec<Vertex>) {
+        let [left, top, right, bottom] = self.rect;
+        let [u_left, v_top, u_right, v_bottom] = self.texture_rect;
+        let vertex = |x, y, u, v| Vertex {
+            position: [x, y, self.depth],
+            texture_coords: [u, v, self.layer],
+            color: self.color,
+            fog: self.fog,
+        };
+
+        let top_left = vertex(left, top, u_left, v_top);
+        let top_right = vertex(right, top, u_right, v_top);
+        let bottom_left = vertex(left, bottom, u_left, v_bottom);
+        let bottom_right = vertex(right, bottom, u_right, v_bottom);
+        vertices.extend([
+            top_left,
+            bottom_left,
+            top_right,
+            top_right,
+            bottom_left,
+            bottom_right,
+        ]);
+    }
+}
+
+/// The shader program, buffers and textures of one OpenGL context.
+///
+/// The OpenGL objects are deleted when the renderer is dropped, so it may only be dropped
+/// while its context is current.
+pub(crate) struct GpuRenderer {
+    program: GLuint,
+    vertex_array: GLuint,
+    vertex_buffer: GLuint,
+    screen_size_location: GLint,
+    alpha_cutoff_location: GLint,
+    background_texture: GLuint,
+    wall_textures: GLuint,
+    sprite_texture: GLuint,
+    target_framebuffer: GLuint,
+    target_color: GLuint,
+    target_depth: GLuint,
+    /// The size of the offscreen target, (0, 0) before it is first used.
+    target_size: (i32, i32),
+    /// The texture layer of every tile with a wall texture.
+    wall_layers: HashMap<u8, f32>,
+    /// The revision of the uploaded textures, `None` before the first upload.
+    textures_revision: Option<u64>,
+    /// The floor and ceiling of the last frame cast on the CPU, reused from frame to frame.
+    background: Image,
+    /// The walls of the last frame, reused from frame to frame.
+    walls: Vec<Option<WallColumn>>,
+    /// The vertices of the batch being built.
+    vertices: Vec<Vertex>,
+}
+
+impl GpuRenderer {
+    /// Compiles the shader program and creates the buffers and textures.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<GpuRenderer, String>` - The renderer, or an error if the OpenGL 3.3
+    ///   functions aren't loaded or the shaders don't compile.
+    ///
+    /// # Safety
+    ///
+    /// This function uses unsafe OpenGL calls and needs a current OpenGL context.
+    unsafe fn new() -> Result<Self, String> {
+        unsafe {
+            if !gl::CreateShader::is_loaded()
+                || !gl::GenVertexArrays::is_loaded()
+                || !gl::TexImage3D::is_loaded()
+                || !gl::BlitFramebuffer::is_loaded()
+            {
+                return Err("the OpenGL 3.3 functions aren't loaded".to_string());
+            }
+
+            let program = link_program(VERTEX_SHADER, FRAGMENT_SHADER)?;
+            let screen_size_location =
+                gl::GetUniformLocation(program, c"screen_size".as_ptr() as *const GLchar);
+            let alpha_cutoff_location =
+                gl::GetUniformLocation(program, c"alpha_cutoff".as_ptr() as *const GLchar);
+
+            let mut vertex_array = 0;
+            let mut vertex_buffer = 0;
+            gl::GenVertexArrays(1, &mut vertex_array);
+            gl::GenBuffers(1, &mut vertex_buffer);
+            gl::BindVertexArray(vertex_array);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vertex_buffer);
+
+            let stride = mem::size_of::<Vertex>() as GLsizei;
+            let attributes = [
+                (3, mem::offset_of!(Vertex, position)),
+                (3, mem::offset_of!(Vertex, texture_coords)),
+                (3, mem::offset_of!(Vertex, color)),
+                (4, mem::offset_of!(Vertex, fog)),
+            ];
+            for (index, (size, offset)) in attributes.into_iter().enumerate() {
+                gl::EnableVertexAttribArray(index as GLuint);
+                gl::VertexAttribPointer(
+                    index as GLuint,
+                    size,
+                    gl::FLOAT,
+                    gl::FALSE,
+                    stride,
+                    offset as *const c_void,
+                );
+            }
+
+            gl::BindVertexArray(0);
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+
+            // The offscreen target gets its storage when it is first bound
+            let mut target_framebuffer = 0;
+            let mut target_renderbuffers = [0; 2];
+            gl::GenFramebuffers(1, &mut target_framebuffer);
+            gl::GenRenderbuffers(2, target_renderbuffers.as_mut_ptr());
+            let [target_color, target_depth] = target_renderbuffers;
+
+            Ok(Self {
+                program,
+                vertex_array,
+                vertex_buffer,
+                screen_size_location,
+                alpha_cutoff_location,
+                background_texture: create_texture(gl::CLAMP_TO_EDGE),
+                wall_textures: create_texture(gl::REPEAT),
+                sprite_texture: create_texture(gl::CLAMP_TO_EDGE),
+                target_framebuffer,
+                target_color,
+                target_depth,
+                target_size: (0, 0),
+                wall_layers: HashMap::new(),
+                textures_revision: None,
+                background: Image::filled(0, 0, Color::RGB(0, 0, 0)),
+                walls: Vec::new(),
+                vertices: Vec::new(),
+            })
+        }
+    }
+
+    /// Renders a 2D scene, see [`render_2d`](super::render_2d).
+    ///
+    /// # Safety
+    ///
+    /// This function uses unsafe OpenGL calls and should be used carefully.
+    pub(crate) unsafe fn render_2d(
+        &mut self,
+        map: &Map,
+        camera: &Camera,
+        screen_width: i32,
+        screen_height: i32,
+    ) {
+        unsafe {
+            self.begin(screen_width, screen_height);
+            clear_screen();
+            self.draw_map_and_player(map, camera);
+            self.end();
+        }
+    }
+
+    /// Renders a 3D scene, see [`render_3d`](super::render_3d).
+    ///
+    /// # Safety
+    ///
+    /// This function uses unsafe OpenGL calls and should be used carefully.
+    pub(crate) unsafe fn render_3d(
+        &mut self,
+        map: &Map,
+        textures: &Textures,
+        sprites: &[Sprite],
+        camera: &Camera,
+        screen_width: i32,
+        screen_height: i32,
+    ) -> DepthBuffer {
+        unsafe {
+            self.begin(screen_width, screen_height);
+            let depth = self.draw_3d(map, textures, sprites, camera, screen_width, screen_height);
+            self.end();
+
+            depth
+        }
+    }
+
+    /// Renders a 3D scene with the 2D map and the player on top, see
+    /// [`debug_render`](super::debug_render).
+    ///
+    /// # Safety
+    ///
+    /// This function uses unsafe OpenGL calls and should be used carefully.
+    pub(crate) unsafe fn debug_render(
+        &mut self,
+        map: &Map,
+        textures: &Textures,
+        sprites: &[Sprite],
+        camera: &Camera,
+        screen_width: i32,
+        screen_height: i32,
+    ) -> DepthBuffer {
+        unsafe {
+            self.begin(screen_width, screen_height);
+            let depth = self.draw_3d(map, textures, sprites, camera, screen_width, screen_height);
+            self.draw_map_and_player(map, camera);
+            self.end();
+
+            depth
+        }
+    }
+
+    /// Draws square markers on top of the current frame, see
+    /// [`draw_markers`](super::draw_markers).
+    ///
+    /// # Safety
+    ///
+    /// This function uses unsafe OpenGL calls and should be used carefully.
+    pub(crate) unsafe fn draw_markers(
+        &mut self,
+        points: &[(f32, f32)],
+        size: f32,
+        color: Color,
+        screen_width: i32,
+        screen_height: i32,
+    ) {
+        unsafe {
+            self.begin(screen_width, screen_height);
+            for &(x, y) in points {
+                Quad::colored(x - size / 2.0, y - size / 2.0, size, size, color)
+                    .push(&mut self.vertices);
+            }
+            self.draw_batch(self.background_texture, OPAQUE);
+            self.end();
+        }
+    }
+
+    /// Renders everything drawn after it into the offscreen target instead of the screen,
+    /// until [`present_target`](Self::present_target) is called.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The width of the target in pixels.
+    /// * `height` - The height of the target in pixels.
+    ///
+    /// # Safety
+    ///
+    /// This function uses unsafe OpenGL calls and should be used carefully.
+    pub(crate) unsafe fn bind_target(&mut self, width: i32, height: i32) {
+        unsafe {
+            if self.target_size != (width, height) {
+                gl::BindRenderbuffer(gl::RENDERBUFFER, self.target_color);
+                gl::RenderbufferStorage(gl::RENDERBUFFER, gl::RGBA8, width, height);
+                gl::BindRenderbuffer(gl::RENDERBUFFER, self.target_depth);
+                gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH_COMPONENT24, width, height);
+                gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+
+                gl::BindFramebuffer(gl::FRAMEBUFFER, self.target_framebuffer);
+                gl::FramebufferRenderbuffer(
+                    gl::FRAMEBUFFER,
+                    gl::COLOR_ATTACHMENT0,
+                    gl::RENDERBUFFER,
+                    self.target_color,
+                );
+                gl::FramebufferRenderbuffer(
+                    gl::FRAMEBUFFER,
+                    gl::DEPTH_ATTACHMENT,
+                    gl::RENDERBUFFER,
+                    self.target_depth,
+                );
+                self.target_size = (width, height);
+            }
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.target_framebuffer);
+        }
+    }
+
+    /// Clears the screen and copies the offscreen target to it with the nearest pixel.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - The x- and y-coordinate, width and height of the rectangle of the
+    ///   screen the target is stretched over.
+    /// * `screen_width` - The width of the screen.
+    /// * `screen_height` - The height of the screen.
+    ///
+    /// # Safety
+    ///
+    /// This function uses unsafe OpenGL calls and should be used carefully.
+    pub(crate) unsafe fn present_target(
+        &mut self,
+        destination: (i32, i32, i32, i32),
+        screen_width: i32,
+        screen_height: i32,
+    ) {
+        unsafe {
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, 0);
+            setup_viewport(screen_width, screen_height);
+            clear_screen();
+
+            // Framebuffer rows count up from the bottom edge
+            let (x, y, width, height) = destination;
+            let (target_width, target_height) = self.target_size;
+            gl::BlitFramebuffer(
+                0,
+                0,
+                target_width,
+                target_height,
+                x,
+                screen_height - y - height,
+                x + width,
+                screen_height - y,
+                gl::COLOR_BUFFER_BIT,
+                gl::NEAREST,
+            );
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+    }
+
+    /// Sets up the viewport and binds the shader program and the vertex array.
+    ///
+    /// The fixed-function matrices are set up as well, so text and overlays can still be
+    /// drawn with them after the frame.
+    unsafe fn begin(&mut self, screen_width: i32, screen_height: i32) {
+        unsafe {
+            setup_viewport(screen_width, screen_height);
+            gl::UseProgram(self.program);
+            gl::BindVertexArray(self.vertex_array);
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::Uniform2f(
+                self.screen_size_location,
+                screen_width as f32,
+                screen_height as f32,
+            );
+        }
+    }
+
+    /// Unbinds everything bound by [`begin`](Self::begin) and the passes.
+    unsafe fn end(&mut self) {
+        unsafe {
+            gl::Disable(gl::DEPTH_TEST);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, 0);
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindVertexArray(0);
+            gl::UseProgram(0);
+        }
+    }
+
+    /// Draws the floor and ceiling, the walls and the sprites of the 3D view.
+    unsafe fn draw_3d(
+        &mut self,
+        map: &Map,
+        textures: &Textures,
+        sprites: &[Sprite],
+        camera: &Camera,
+        screen_width: i32,
+        screen_height: i32,
+    ) -> DepthBuffer {
+        unsafe {
+            self.upload_textures(textures);
+
+            let width = screen_width.max(0) as u32;
+            let height = screen_height.max(0) as u32;
+            if casts_floor(map) {
+                // Per-tile floors are cast on the CPU and drawn as one quad behind everything
+                if self.background.width != width || self.background.height != height {
+                    self.background = Image::filled(width, height, Color::RGB(0, 0, 0));
+                }
+                draw_floor_and_ceiling(map, textures, camera, &mut self.background);
+                upload_layers(self.background_texture, width, height, &[&self.background]);
+
+                Quad {
+                    texture_rect: [0.0, 0.0, 1.0, 1.0],
+                    layer: 0.0,
+                    ..Quad::colored(0.0, 0.0, width as f32, height as f32, Color::WHITE)
+                }
+                .push(&mut self.vertices);
+            } else {
+                self.push_floor_and_ceiling(map, camera, width, height);
+            }
+            self.draw_batch(self.background_texture, OPAQUE);
+
+            gl::Enable(gl::DEPTH_TEST);
+            gl::DepthFunc(gl::LEQUAL);
+            gl::DepthMask(gl::TRUE);
+            gl::Clear(gl::DEPTH_BUFFER_BIT);
+
+            let depth = self.push_walls(map, camera, width, height);
+            self.draw_batch(self.wall_textures, OPAQUE);
+
+            self.push_sprites(map, textures, sprites, camera, width, height);
+            self.draw_batch(self.sprite_texture, SPRITE_ALPHA_CUTOFF);
+
+            gl::Disable(gl::DEPTH_TEST);
+            depth
+        }
+    }
+
+    /// Adds the flat coloured ceiling and floor to the batch.
+    ///
+    /// Rows are shaded like in the software renderer, consecutive rows with the same colour
+    /// share one quad. Without fog these are one quad for the ceiling and one for the floor.
+    fn push_floor_and_ceiling(
+        &mut self,
+        map: &Map,
+        camera: &Camera,
+        screen_width: u32,
+        screen_height: u32,
+    ) {
+        let mut rows = (0..screen_height)
+            .map(|y| (y, flat_row_color(map, camera, y, screen_height)))
+            .peekable();
+        while let Some((top, color)) = rows.next() {
+            let mut bottom = top + 1;
+            while let Some((y, _)) = rows.next_if(|&(_, next)| next == color) {
+                bottom = y + 1;
+            }
+
+            let height = (bottom - top) as f32;
+            Quad::colored(0.0, top as f32, screen_width as f32, height, color)
+                .push(&mut self.vertices);
+        }
+    }
+
+    /// Casts the walls of every screen column and adds one quad per column to the batch.
+    ///
+    /// Walls are shaded like in the software renderer: darker when hit on a horizontal grid
+    /// line and faded into the fog of the map with distance.
+    ///
+    /// # Returns
+    ///
+    /// The depth buffer with the perpendicular distance of the wall in every screen column.
+    fn push_walls(
+        &mut self,
+        map: &Map,
+        camera: &Camera,
+        screen_width: u32,
+        screen_height: u32,
+    ) -> DepthBuffer {
+        self.walls.clear();
+        self.walls.resize(screen_width as usize, None);
+        cast_walls_into(map, camera, screen_height as i32, &mut self.walls);
+
+        let mut depth = DepthBuffer::new(self.walls.len());
+
+        for (column, wall) in self.walls.iter().enumerate() {
+            // Rays that left the map don't hit a wall
+            let Some(wall) = wall else {
+                continue;
+            };
+            depth.set(column, wall.distance);
+
+            let brightness = side_brightness(wall.hit.side);
+            let (layer, base) = match self.wall_layers.get(&wall.hit.tile) {
+                Some(&layer) => (layer, [1.0; 3]),
+                None => {
+                    let wall_color = map
+                        .tile_type(wall.hit.tile)
+                        .map_or(UNTEXTURED_WALL_COLOR, |tile_type| tile_type.wall_color());
+                    (UNTEXTURED, rgb(wall_color))
+                }
+            };
+
+            Quad {
+                rect: [column as f32, wall.top, column as f32 + 1.0, wall.bottom],
+                depth: depth_of(wall.distance, map.cube_size()),
+                texture_rect: [wall.hit.offset, 0.0, wall.hit.offset, 1.0],
+                layer,
+                color: base.map(|channel| channel * brightness),
+                fog: fog_of(map, wall.distance),
+            }
+            .push(&mut self.vertices);
+        }
+
+        depth
+    }
+
+    /// Adds one quad per visible sprite to the batch.
+    ///
+    /// Sprites are depth tested against the walls and each other per pixel, so they can be
+    /// drawn in any order.
+    fn push_sprites(
+        &mut self,
+        map: &Map,
+        textures: &Textures,
+        sprites: &[Sprite],
+        camera: &Camera,
+        screen_width: u32,
+        screen_height: u32,
+    ) {
+        let Some(sprite_sheet) = textures.sprite_sheet() else {
+            return;
+        };
+        let aspect = sprite_sheet.sprite_width as f32 / sprite_sheet.sprite_height as f32;
+
+        for sprite in sprites {
+            let Some(texture_rect) = sprite_sheet.texture_rect(sprite.sprite_id) else {
+                continue;
+            };
+            let Some(projection) =
+                project_sprite(sprite, map.cube_size(), camera, screen_width, screen_height)
+            else {
+                continue;
+            };
+
+            let half_width = (projection.bottom - projection.top) * aspect / 2.0;
+            let (left, top, right, bottom) = texture_rect;
+            Quad {
+                rect: [
+                    projection.center - half_width,
+                    projection.top,
+                    projection.center + half_width,
+                    projection.bottom,
+                ],
+                depth: depth_of(projection.distance, map.cube_size()),
+                texture_rect: [left, top, right, bottom],
+                layer: 0.0,
+                color: [1.0; 3],
+                fog: fog_of(map, projection.distance),
+            }
+            .push(&mut self.vertices);
+        }
+    }
+
+    /// Adds the tiles of the 2D map and the player to the batch and draws it.
+    unsafe fn draw_map_and_player(&mut self, map: &Map, camera: &Camera) {
+        unsafe {
+            let rects =
+                map_rects_2d(map)
+                    .into_iter()
+                    .chain(player_rects(camera.x, camera.y, camera.angle));
+            for (x, y, width, height, color) in rects {
+                Quad::colored(x as f32, y as f32, width as f32, height as f32, color)
+                    .push(&mut self.vertices);
+            }
+            self.draw_batch(self.background_texture, OPAQUE);
+        }
+    }
+
+    /// Uploads the batch to the vertex buffer, draws it with one draw call and clears it.
+    ///
+    /// # Arguments
+    ///
+    /// * `texture` - The texture array the batch is textured with.
+    /// * `alpha_cutoff` - Pixels with at most this alpha are discarded.
+    unsafe fn draw_batch(&mut self, texture: GLuint, alpha_cutoff: f32) {
+        if self.vertices.is_empty() {
+            return;
+        }
+
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vertex_buffer);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                mem::size_of_val(self.vertices.as_slice()) as GLsizeiptr,
+                self.vertices.as_ptr() as *const c_void,
+                gl::STREAM_DRAW,
+            );
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, texture);
+            gl::Uniform1f(self.alpha_cutoff_location, alpha_cutoff);
+            gl::DrawArrays(gl::TRIANGLES, 0, self.vertices.len() as GLsizei);
+        }
+
+        self.vertices.clear();
+    }
+
+    /// Uploads the wall textures and the sprite sheet if they changed since the last upload.
+    ///
+    /// All wall textures go into one texture array so the walls can be drawn with a single
+    /// draw call. Its layers are as large as the largest texture, smaller textures are
+    /// scaled up with the nearest pixel.
+    unsafe fn upload_textures(&mut self, textures: &Textures) {
+        if self.textures_revision == Some(textures.revision()) {
+            return;
+        }
+
+        let tiles = (0..=u8::MAX)
+            .filter_map(|tile| Some((tile, textures.wall(tile)?)))
+            .collect::<Vec<_>>();
+        let width = tiles
+            .iter()
+            .map(|(_, image)| image.width)
+            .max()
+            .unwrap_or(1);
+        let height = tiles
+            .iter()
+            .map(|(_, image)| image.height)
+            .max()
+            .unwrap_or(1);
+        let layers = tiles.iter().map(|&(_, image)| image).collect::<Vec<_>>();
+
+        self.wall_layers = tiles
+            .iter()
+            .enumerate()
+            .map(|(layer, &(tile, _))| (tile, layer as f32))
+            .collect();
+
+        unsafe {
+            upload_layers(self.wall_textures, width, height, &layers);
+            if let Some(sprite_sheet) = textures.sprite_sheet() {
+                let image = &sprite_sheet.image;
+                upload_layers(self.sprite_texture, image.width, image.height, &[image]);
+            }
+        }
+
+        self.textures_revision = Some(textures.revision());
+    }
+}
+
+impl Drop for GpuRenderer {
+    /// Deletes the OpenGL objects of the renderer, its context has to be current.
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteProgram(self.program);
+            gl::DeleteVertexArrays(1, &self.vertex_array);
+            gl::DeleteBuffers(1, &self.vertex_buffer);
+            let textures = [
+                self.background_texture,
+                self.wall_textures,
+                self.sprite_texture,
+            ];
+            gl::DeleteTextures(textures.len() as GLsizei, textures.as_ptr());
+            gl::DeleteFramebuffers(1, &self.target_framebuffer);
+            let renderbuffers = [self.target_color, self.target_depth];
+            gl::DeleteRenderbuffers(renderbuffers.len() as GLsizei, renderbuffers.as_ptr());
+        }
+    }
+}
+
+/// Returns the depth of something at a perpendicular distance from the camera.
+///
+/// The depth grows from -1 at the camera towards 1 far away, with most of the precision close
+/// to the camera.
+fn depth_of(distance: f32, cube_size: f32) -> f32 {
+    let distance = distance.max(0.0);
+    2.0 * distance / (distance + cube_size) - 1.0
+}
+
+/// Returns the fog colour of the map and how much of it covers something at a distance.
+fn fog_of(map: &Map, distance: f32) -> [f32; 4] {
+    match map.metadata().fog {
+        Some(fog) => {
+            let [r, g, b] = rgb(fog.color);
+            [r, g, b, fog.factor(distance / map.cube_size())]
+        }
+        None => [0.0; 4],
+    }
+}
+
+/// Converts a colour to red, green and blue from 0 to 1.
+fn rgb(color: Color) -> [f32; 3] {
+    [color.r, color.g, color.b].map(|channel| channel as f32 / 255.0)
+}
+
+/// Creates a texture array that is sampled with the nearest pixel.
+///
+/// # Arguments
+///
+/// * `wrap` - How texture coordinates outside of 0 to 1 are wrapped.
+unsafe fn create_texture(wrap: GLenum) -> GLuint {
+    unsafe {
+        let mut texture = 0;
+        gl::GenTextures(1, &mut texture);
+        gl::BindTexture(gl::TEXTURE_2D_ARRAY, texture);
+        gl::TexParameteri(
+            gl::TEXTURE_2D_ARRAY,
+            gl::TEXTURE_MIN_FILTER,
+            gl::NEAREST as GLint,
+        );
+        gl::TexParameteri(
+            gl::TEXTURE_2D_ARRAY,
+            gl::TEXTURE_MAG_FILTER,
+            gl::NEAREST as GLint,
+        );
+        gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_S, wrap as GLint);
+        gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_T, wrap as GLint);
+        gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_MAX_LEVEL, 0);
+        gl::BindTexture(gl::TEXTURE_2D_ARRAY, 0);
+        texture
+    }
+}
+
+/// Replaces the layers of a texture array with images.
+///
+/// Images of another size than the layers are scaled with the nearest pixel. An empty list
+/// uploads a single transparent layer, so the texture is always complete.
+///
+/// # Arguments
+///
+/// * `texture` - The texture array to upload into.
+/// * `width` - The width of the layers.
+/// * `height` - The height of the layers.
+/// * `images` - The image of every layer.
+unsafe fn upload_layers(texture: GLuint, width: u32, height: u32, images: &[&Image]) {
+    let (width, height) = (width.max(1), height.max(1));
+    let mut pixels = Vec::with_capacity((width * height * 4) as usize * images.len().max(1));
+
+    for image in images {
+        if image.width == width && image.height == height {
+            pixels.extend_from_slice(&image.pixels);
+            continue;
+        }
+
+        for y in 0..height {
+            for x in 0..width {
+                let u = (x as f32 + 0.5) / width as f32;
+                let v = (y as f32 + 0.5) / height as f32;
+                let color = image.sample(u, v);
+                pixels.extend([color.r, color.g, color.b, color.a]);
+            }
+        }
+    }
+    if images.is_empty() {
+        pixels.resize((width * height * 4) as usize, 0);
+    }
+
+    unsafe {
+        gl::BindTexture(gl::TEXTURE_2D_ARRAY, texture);
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+        gl::TexImage3D(
+            gl::TEXTURE_2D_ARRAY,
+            0,
+            gl::RGBA8 as GLint,
+            width as GLsizei,
+            height as GLsizei,
+            images.len().max(1) as GLsizei,
+            0,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            pixels.as_ptr() as *const c_void,
+        );
+    }
+}
+
+/// Compiles a vertex and a fragment shader and links them into a program.
+///
+/// # Returns
+///
+/// * `Result<GLuint, String>` - The program, or the compile or link log of the driver.
+unsafe fn link_program(vertex_source: &str, fragment_source: &str) -> Result<GLuint, String> {
+    unsafe {
+        let vertex = compile_shader(gl::VERTEX_SHADER, vertex_source)?;
+        let fragment = match compile_shader(gl::FRAGMENT_SHADER, fragment_source) {
+            Ok(fragment) => fragment,
+            Err(e) => {
+                gl::DeleteShader(vertex);
+                return Err(e);
+            }
+        };
+
+        let program = gl::CreateProgram();
+        gl::AttachShader(program, vertex);
+        gl::AttachShader(program, fragment);
+        gl::LinkProgram(program);
+        gl::DeleteShader(vertex);
+        gl::DeleteShader(fragment);
+
+        let mut status = 0;
+        gl::GetProgramiv(program, gl::LINK_STATUS, &mut status);
+        if status != gl::TRUE as GLint {
+            let log = info_log(program, gl::GetProgramiv, gl::GetProgramInfoLog);
+            gl::DeleteProgram(program);
+            return Err(format!("the shader program doesn't link: {}", log));
+        }
+
+        Ok(program)
+    }
+}
+
+/// Compiles a shader.
+///
+/// # Returns
+///
+/// * `Result<GLuint, String>` - The shader, or the compile log of the driver.
+unsafe fn compile_shader(kind: GLenum, source: &str) -> Result<GLuint, String> {
+    unsafe {
+        let source = CString::new(source).map_err(|e| e.to_string())?;
+        let shader = gl::CreateShader(kind);
+        gl::ShaderSource(shader, 1, &source.as_ptr(), ptr::null());
+        gl::CompileShader(shader);
+
+        let mut status = 0;
+        gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut status);
+        if status != gl::TRUE as GLint {
+            let log = info_log(shader, gl::GetShaderiv, gl::GetShaderInfoLog);
+            gl::DeleteShader(shader);
+            return Err(format!("a shader doesn't compile: {}", log));
+        }
+
+        Ok(shader)
+    }
+}
+
+/// Reads the info log of a shader or a program.
+unsafe fn info_log(
+    object: GLuint,
+    get_parameter: unsafe fn(GLuint, GLenum, *mut GLint),
+    get_log: unsafe fn(GLuint, GLsizei, *mut GLsizei, *mut GLchar),
+) -> String {
+    unsafe {
+        let mut length = 0;
+        get_parameter(object, gl::INFO_LOG_LENGTH, &mut length);
+
+        let mut log = vec![0u8; length.max(1) as usize];
+        let mut written = 0;
+        get_log(
+            object,
+            log.len() as GLsizei,
+            &mut written,
+            log.as_mut_ptr() as *mut GLchar,
+        );
+        log.truncate(written.max(0) as usize);
+
+        String::from_utf8_lossy(&log).trim_end().to_string()
+    }
+}
@@ -0,0 +1,177 @@
+//! # Image Module
+//!
+//! This module provides [`Image`], a plain RGBA image in memory. It is used for textures and
+//! as the frame the software renderer draws into, and can be loaded from and saved to image
+//! files on disk.
+
+use crate::utilities::png::{decode_png, encode_png};
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::surface::Surface;
+use std::path::Path;
+use std::*;
+
+/// An RGBA image with 8 bits per channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    /// The width of the image in pixels.
+    pub width: u32,
+    /// The height of the image in pixels.
+    pub height: u32,
+    /// The pixels in row-major order from the top-left corner, 4 bytes (R, G, B, A) per pixel.
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Creates an image from RGBA pixel data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pixels` doesn't hold exactly `width * height * 4` bytes.
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Self {
+        assert_eq!(
+            pixels.len(),
+            width as usize * height as usize * 4,
+            "image data doesn't match its size"
+        );
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Creates an image filled with a single colour.
+    pub fn filled(width: u32, height: u32, color: Color) -> Self {
+        let pixels = [color.r, color.g, color.b, color.a].repeat(width as usize * height as usize);
+        Self::new(width, height, pixels)
+    }
+
+    /// Loads an image from a BMP or PNG file, picked by the file extension.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the image file.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Image, String>` - The image, or an error if the file can't be read or decoded.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let is_png = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("png"));
+        if is_png {
+            return fs::read(path)
+                .map_err(|e| e.to_string())
+                .and_then(|data| decode_png(&data))
+                .map_err(|e| format!("{}: {}", path.display(), e));
+        }
+
+        let surface = Surface::load_bmp(path)
+            .and_then(|surface| surface.convert_format(PixelFormatEnum::RGBA32))
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+        let width = surface.width();
+        let height = surface.height();
+        let pitch = surface.pitch() as usize;
+        let row_length = width as usize * 4;
+
+        let mut pixels = Vec::with_capacity(row_length * height as usize);
+        surface.with_lock(|data| {
+            for row in data.chunks(pitch).take(height as usize) {
+                pixels.extend_from_slice(&row[..row_length]);
+            }
+        });
+
+        Ok(Self::new(width, height, pixels))
+    }
+
+    /// Saves the image as a PNG file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the image file, it is overwritten if it exists.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - An error if the image can't be encoded or the file can't be
+    ///   written.
+    pub fn save_png(&self, path: &Path) -> Result<(), String> {
+        encode_png(self)
+            .and_then(|data| fs::write(path, data).map_err(|e| e.to_string()))
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Returns the colour of a pixel, or `None` if it lies outside of the image.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let index = (y as usize * self.width as usize + x as usize) * 4;
+        let pixel = &self.pixels[index..index + 4];
+        Some(Color::RGBA(pixel[0], pixel[1], pixel[2], pixel[3]))
+    }
+
+    /// Sets the colour of a pixel, pixels outside of the image are ignored.
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: Color) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+
+        let index = (y as usize * self.width as usize + x as usize) * 4;
+        self.pixels[index..index + 4].copy_from_slice(&[color.r, color.g, color.b, color.a]);
+    }
+
+    /// Fills the whole image with a single colour.
+    pub fn fill(&mut self, color: Color) {
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+    }
+
+    /// Fills a rectangle with a single colour, the parts outside of the image are clipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The left edge of the rectangle in pixels.
+    /// * `y` - The top edge of the rectangle in pixels.
+    /// * `width` - The width of the rectangle in pixels.
+    /// * `height` - The height of the rectangle in pixels.
+    /// * `color` - The colour to fill the rectangle with.
+    pub fn fill_rect(&mut self, x: i32, y: i32, width: i32, height: i32, color: Color) {
+        let clip = |start: i32, length: i32, size: u32| {
+            let end = start.saturating_add(length.max(0)).clamp(0, size as i32) as usize;
+            (start.clamp(0, size as i32) as usize)..end
+        };
+        let columns = clip(x, width, self.width);
+        let rows = clip(y, height, self.height);
+        let row_length = self.width as usize * 4;
+
+        for row in rows {
+            let line = &mut self.pixels[row * row_length..(row + 1) * row_length];
+            for pixel in line[columns.start * 4..columns.end * 4].chunks_exact_mut(4) {
+                pixel.copy_from_slice(&[color.r, color.g, color.b, color.a]);
+            }
+        }
+    }
+
+    /// Samples the image with texture coordinates, using the nearest pixel.
+    ///
+    /// The coordinates wrap around, so `(0.0, 0.0)` and `(1.0, 1.0)` both give the
+    /// top-left pixel.
+    ///
+    /// # Arguments
+    ///
+    /// * `u` - The horizontal texture coordinate, 0 is the left edge and 1 the right edge.
+    /// * `v` - The vertical texture coordinate, 0 is the top edge and 1 the bottom edge.
+    pub fn sample(&self, u: f32, v: f32) -> Color {
+        if self.width == 0 || self.height == 0 {
+            return Color::RGBA(0, 0, 0, 0);
+        }
+
+        let x = ((u.rem_euclid(1.0) * self.width as f32) as u32).min(self.width - 1);
+        let y = ((v.rem_euclid(1.0) * self.height as f32) as u32).min(self.height - 1);
+        self.pixel(x, y).unwrap_or(Color::RGBA(0, 0, 0, 0))
+    }
+}
@@ -2,37 +2,53 @@
 //!
 //! This module provides functionality for rendering a 2D map.
 
-use crate::rrm::{MAP_CUBE_SIZE, MAP_DATA, MAP_HEIGHT, MAP_WIDTH};
-use glu_sys::*;
+use super::image::Image;
+use crate::rrm::Map;
+use sdl2::pixels::Color;
 use std::*;
 
-/// Renders the 2D map from the rrm file.
+/// Draws the 2D map from the rrm file into a frame.
 ///
-/// # Safety
+/// Every tile is drawn in the map colour of its tile type from the map's palette, one world
+/// unit per pixel with a one pixel gap between the tiles.
 ///
-/// This function uses unsafe OpenGL calls and should be used carefully.
-pub unsafe fn draw_map_2d() {
-    for y in 0..MAP_HEIGHT {
-        for x in 0..MAP_WIDTH {
-            if MAP_DATA[y][x] == 1 {
-                glColor3f(1.0, 1.0, 1.0);
-            } else {
-                glColor4f(0.0, 0.0, 0.0, 0.0);
-            }
+/// # Arguments
+///
+/// * `frame` - The image to draw into.
+/// * `map` - The map to render.
+pub fn draw_map_2d(frame: &mut Image, map: &Map) {
+    for (x, y, width, height, color) in map_rects_2d(map) {
+        frame.fill_rect(x, y, width, height, color);
+    }
+}
 
-            let x_offset = ((x as f32) * MAP_CUBE_SIZE) as i32;
-            let y_offset = ((y as f32) * MAP_CUBE_SIZE) as i32;
-            let map_cube_size_i32 = MAP_CUBE_SIZE as i32;
+/// Returns the rectangles the 2D map is drawn with, as `(x, y, width, height, colour)`.
+pub(crate) fn map_rects_2d(map: &Map) -> Vec<(i32, i32, i32, i32, Color)> {
+    let cube_size = map.cube_size();
+    let mut rects = Vec::with_capacity(map.data().len());
 
-            glBegin(GL_QUADS);
-            glVertex2i(x_offset + 1, y_offset + 1);
-            glVertex2i(x_offset + 1, y_offset + map_cube_size_i32 - 1);
-            glVertex2i(
-                x_offset + map_cube_size_i32 - 1,
-                y_offset + map_cube_size_i32 - 1,
-            );
-            glVertex2i(x_offset + map_cube_size_i32 - 1, y_offset + 1);
-            glEnd();
+    for (y, row) in map.data().chunks(map.width().max(1)).enumerate() {
+        for (x, &tile) in row.iter().enumerate() {
+            let color = map
+                .tile_type(tile)
+                .map_or(Color::RGB(0, 0, 0), |tile_type| {
+                    let color = tile_type.map_color;
+                    Color::RGB(color.r, color.g, color.b)
+                });
+
+            let x_offset = ((x as f32) * cube_size) as i32;
+            let y_offset = ((y as f32) * cube_size) as i32;
+            let map_cube_size_i32 = cube_size as i32;
+
+            rects.push((
+                x_offset + 1,
+                y_offset + 1,
+                map_cube_size_i32 - 2,
+                map_cube_size_i32 - 2,
+                color,
+            ));
         }
     }
+
+    rects
 }
@@ -5,44 +5,100 @@
 //!
 //! ## Components
 //!
+//! - [`camera`]: The point of view with field of view, pitch and near clip
+//! - [`depth_buffer`]: Per-column wall distances of the 3D view for depth testing
+//! - [`image`]: RGBA images used for textures and as the frame the views are rendered into
 //! - [`map`]: Handles rendering of 2D map layouts
 //! - [`player`]: Player entity rendering in both 2D and 3D contexts
+//! - [`raycast`](mod@raycast): Ray-hit queries against the map walls, shared by the renderer and gameplay code
+//! - [`render_scale`]: The resolution the 3D view is rendered at and how it is scaled to the window
 //! - [`renderer`]: Core rendering system with support for both 2D and 3D scenes
+//! - [`software`]: CPU rasterizer rendering the 2D and 3D views into an image, also headless
+//! - [`sprite`]: Billboard sprites and the sprite sheet they are looked up in
 //! - [`text`]: Text rendering system using custom fonts
+//! - [`texture`]: Wall and floor textures loaded from the map palette
 //!
 //! ## Features
 //!
-//! - Ray-casting based 3D rendering with configurable FOV
+//! - Ray-casting based 3D rendering with a configurable camera (FOV, pitch, near clip)
 //! - 2D overhead map view for debugging
 //! - Custom text rendering with TrueType font support
-//! - Perspective-correct wall rendering
+//! - Perspective-correct wall rendering with rays spaced evenly on a camera plane
+//! - Rays cast in parallel across screen columns with rayon
+//! - Textured walls using the exact hit offset along the wall
+//! - Billboard sprites for entities, hidden behind closer walls
+//! - A per-column depth buffer returned by the 3D pass for custom overlays
+//! - Flat or per-tile textured floors and a flat ceiling, faded into the fog of the map
 //! - Debug rendering mode for development
+//! - Walls, sprites and the 2D map batched into one draw call per pass with OpenGL 3.3 shaders,
+//!   with a fixed-function fallback for old drivers (`fixed-function` feature)
+//! - Headless rendering into an RGBA image without an OpenGL context
+//! - Lower render resolutions scaled up with the nearest pixel, optionally by whole multiples
 //!
 //! ## Example
 //!
-//! ```rust
+//! ```rust,no_run
 //! use rust_ray::rendering::{
 //!     render_2d,
 //!     render_3d,
+//!     Camera,
 //!     TextRenderer,
+//!     Textures,
 //! };
+//! use rust_ray::map_initialize;
 //! use sdl2::pixels::Color;
 //!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! # let (screen_width, screen_height) = (1280, 720);
 //! // Initialize text renderer
 //! let text_renderer = TextRenderer::new("assets/fonts/your_font.ttf", Color::RGB(255, 255, 255))?;
 //!
-//! // Render a frame
-//! render_3d(player_x, player_y, player_angle, screen_width, screen_height);
+//! // Load a map
+//! let map = map_initialize("assets/maps")?;
+//! let textures = Textures::load(&map)?;
+//!
+//! // Set up the camera at the player start, e.g. with a wider field of view
+//! let (player_x, player_y, player_angle) = map.player_start().unwrap_or_default();
+//! let mut camera = Camera::new(player_x, player_y, player_angle);
+//! camera.fov = 75f32.to_radians();
+//!
+//! // Render a frame, the depth buffer can be used to hide overlays behind walls
+//! let depth = render_3d(&map, &textures, &[], &camera, screen_width, screen_height);
+//! text_renderer.render_text(10.0, 10.0, "Score: 0", 16.0);
 //!
 //! // Or render in 2D mode
-//! render_2d(player_x, player_y, player_angle, screen_width, screen_height);
+//! render_2d(&map, &camera, screen_width, screen_height);
+//! # let _ = depth;
+//! # Ok(())
+//! # }
 //! ```
 
+pub mod camera;
+pub mod depth_buffer;
+#[cfg(not(feature = "fixed-function"))]
+mod gpu;
+pub mod image;
 pub mod map;
 pub mod player;
+pub mod raycast;
+pub mod render_scale;
 pub mod renderer;
+pub mod software;
+pub mod sprite;
 pub mod text;
+pub mod texture;
 
+pub use camera::Camera;
+pub use depth_buffer::DepthBuffer;
+pub use image::Image;
 pub use player::draw_player;
-pub use renderer::{debug_render, render_2d, render_3d};
+pub use raycast::{raycast, RayHit, WallSide};
+pub use render_scale::RenderScale;
+pub use renderer::{
+    cast_wall, cast_walls, cast_walls_into, debug_render, draw_markers, gpu_error, render_2d,
+    render_3d, render_3d_scaled, WallColumn,
+};
+pub use software::{debug_render_image, render_2d_image, render_3d_image};
+pub use sprite::{Sprite, SpriteSheet};
 pub use text::TextRenderer;
+pub use texture::Textures;
@@ -2,39 +2,53 @@
 //!
 //! This module provides functionality for rendering the player in a 2D environment.
 
-use glu_sys::*;
+use super::image::Image;
+use sdl2::pixels::Color;
 use std::*;
 
-/// Draws the player on the screen.
+// Constants
+const PLAYER_COLOR: Color = Color::RGB(255, 255, 255);
+const PLAYER_SIZE: i32 = 16;
+const DIRECTION_LENGTH: f32 = 24.0;
+
+/// Draws the player into a frame.
+///
+/// The player is drawn as a square with a line pointing in the viewing direction.
 ///
 /// # Arguments
 ///
+/// * `frame` - The image to draw into.
 /// * `player_x` - The player's x-coordinate.
 /// * `player_y` - The player's y-coordinate.
 /// * `player_angle` - The player's viewing angle.
-///
-/// # Safety
-///
-/// This function uses unsafe OpenGL calls and should be used carefully.
-pub fn draw_player(player_x: f32, player_y: f32, player_angle: f32) {
-    let player_x = player_x as i32;
-    let player_y = player_y as i32;
+pub fn draw_player(frame: &mut Image, player_x: f32, player_y: f32, player_angle: f32) {
+    for (x, y, width, height, color) in player_rects(player_x, player_y, player_angle) {
+        frame.fill_rect(x, y, width, height, color);
+    }
+}
+
+/// Returns the rectangles the player is drawn with, as `(x, y, width, height, colour)`.
+pub(crate) fn player_rects(
+    player_x: f32,
+    player_y: f32,
+    player_angle: f32,
+) -> Vec<(i32, i32, i32, i32, Color)> {
+    let mut rects = vec![(
+        player_x as i32 - PLAYER_SIZE / 2,
+        player_y as i32 - PLAYER_SIZE / 2,
+        PLAYER_SIZE,
+        PLAYER_SIZE,
+        PLAYER_COLOR,
+    )];
 
-    // Calculate the angle to the mouse pointer
+    // Step along the direction line one pixel at a time
     let delta_x = player_angle.cos();
     let delta_y = player_angle.sin();
-
-    unsafe {
-        glColor3f(1.0, 1.0, 1.0);
-        glPointSize(16.0);
-        glBegin(GL_POINTS);
-        glVertex2i(player_x, player_y);
-        glEnd();
-
-        glLineWidth(3.00);
-        glBegin(GL_LINES);
-        glVertex2i(player_x, player_y);
-        glVertex2i(player_x + (delta_x as i32), player_y + (delta_y as i32));
-        glEnd();
+    for step in 0..=DIRECTION_LENGTH as i32 {
+        let x = player_x + delta_x * step as f32;
+        let y = player_y + delta_y * step as f32;
+        rects.push((x as i32 - 1, y as i32 - 1, 3, 3, PLAYER_COLOR));
     }
+
+    rects
 }
@@ -0,0 +1,201 @@
+//! # Raycast Module
+//!
+//! This module provides the ray-hit query the 3D renderer is built on. The same query can
+//! be used by gameplay code, e.g. for hitscan weapons, line-of-sight checks or "use"
+//! interactions.
+//!
+//! Rays are cast in world units (see the [`Map`] docs for the coordinate convention) with
+//! a DDA (digital differential analyzer) grid traversal: the ray visits every tile it
+//! passes through exactly once, so the hit point is exact and the number of steps is
+//! bounded by the size of the map.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rust_ray::rendering::raycast;
+//! use rust_ray::Map;
+//!
+//! let map = Map::default();
+//! let origin = (1.5 * map.cube_size(), 1.5 * map.cube_size());
+//!
+//! // Is there a wall within 5 tiles straight ahead?
+//! if let Some(hit) = raycast(&map, origin, 0.0, 5.0 * map.cube_size()) {
+//!     println!("hit tile {} at {:.1} units", hit.tile, hit.distance);
+//! }
+//! ```
+
+use crate::rrm::Map;
+use std::*;
+
+/// Which kind of grid line a ray crossed when it hit a wall.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WallSide {
+    /// The ray crossed a vertical grid line, so it hit the west or east face of a tile.
+    Vertical,
+    /// The ray crossed a horizontal grid line, so it hit the north or south face of a tile.
+    Horizontal,
+}
+
+/// Where a ray hit a wall.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    /// The distance from the ray origin to the hit point in world units.
+    pub distance: f32,
+    /// The x-coordinate of the hit point in world units.
+    pub x: f32,
+    /// The y-coordinate of the hit point in world units.
+    pub y: f32,
+    /// The column of the tile that was hit.
+    pub cell_x: usize,
+    /// The row of the tile that was hit.
+    pub cell_y: usize,
+    /// The id of the tile that was hit.
+    pub tile: u8,
+    /// The kind of grid line the ray crossed.
+    pub side: WallSide,
+    /// The unit normal of the wall face that was hit, pointing back towards the ray origin.
+    pub normal: (f32, f32),
+    /// Where along the wall face the hit is, from 0 at the left edge to 1 at the right edge
+    /// as seen when facing the wall. Used as the horizontal texture coordinate.
+    pub offset: f32,
+}
+
+/// Casts a ray against the walls of a map.
+///
+/// Rays starting outside of the map are clipped to the map bounds first, so they still
+/// find the map. A ray starting inside a wall hits it at distance 0.
+///
+/// # Arguments
+///
+/// * `map` - The map to cast the ray against.
+/// * `origin` - The start of the ray in world units.
+/// * `angle` - The direction of the ray in radians, 0 points along the x-axis.
+/// * `max_distance` - How far the ray reaches in world units, `f32::INFINITY` for no limit.
+///
+/// # Returns
+///
+/// Where the ray hits a wall, or `None` if it leaves the map or reaches `max_distance`
+/// without hitting one.
+pub fn raycast(map: &Map, origin: (f32, f32), angle: f32, max_distance: f32) -> Option<RayHit> {
+    let cube_size = map.cube_size();
+    let dir_x = angle.cos();
+    let dir_y = angle.sin();
+
+    // Work in tile units, one unit of `t` along the ray is one tile
+    let pos_x = origin.0 / cube_size;
+    let pos_y = origin.1 / cube_size;
+    let max_t = max_distance / cube_size;
+
+    // Clip the ray against the map bounds so rays from outside of the map still find it
+    let (enter_x, exit_x) = slab(pos_x, dir_x, map.width() as f32)?;
+    let (enter_y, exit_y) = slab(pos_y, dir_y, map.height() as f32)?;
+    let t_enter = enter_x.max(enter_y).max(0.0);
+    let t_exit = exit_x.min(exit_y);
+    if t_enter >= t_exit || t_enter > max_t {
+        return None;
+    }
+
+    let mut side = if enter_x > enter_y {
+        WallSide::Vertical
+    } else {
+        WallSide::Horizontal
+    };
+    let start_x = pos_x + dir_x * t_enter;
+    let start_y = pos_y + dir_y * t_enter;
+    let mut cell_x = (start_x.floor() as i64).clamp(0, map.width() as i64 - 1);
+    let mut cell_y = (start_y.floor() as i64).clamp(0, map.height() as i64 - 1);
+
+    // Distance along the ray between two vertical or two horizontal grid lines
+    let delta_x = if dir_x == 0.0 {
+        f32::INFINITY
+    } else {
+        (1.0 / dir_x).abs()
+    };
+    let delta_y = if dir_y == 0.0 {
+        f32::INFINITY
+    } else {
+        (1.0 / dir_y).abs()
+    };
+    let step_x = if dir_x < 0.0 { -1 } else { 1 };
+    let step_y = if dir_y < 0.0 { -1 } else { 1 };
+
+    // Distance along the ray to the first vertical and horizontal grid line
+    let mut side_x = if dir_x < 0.0 {
+        (start_x - cell_x as f32) * delta_x
+    } else {
+        (cell_x as f32 + 1.0 - start_x) * delta_x
+    };
+    let mut side_y = if dir_y < 0.0 {
+        (start_y - cell_y as f32) * delta_y
+    } else {
+        (cell_y as f32 + 1.0 - start_y) * delta_y
+    };
+
+    let mut t = t_enter;
+    loop {
+        if t > max_t
+            || cell_x < 0
+            || cell_y < 0
+            || cell_x >= map.width() as i64
+            || cell_y >= map.height() as i64
+        {
+            return None;
+        }
+
+        let (tile_x, tile_y) = (cell_x as usize, cell_y as usize);
+        if map.is_wall(tile_x, tile_y) {
+            let distance = t * cube_size;
+            let (hit_x, hit_y) = (pos_x + dir_x * t, pos_y + dir_y * t);
+
+            // Flip the offset on faces seen from the other side, so textures aren't mirrored
+            let (normal, offset) = match side {
+                WallSide::Vertical if step_x > 0 => ((-1.0, 0.0), hit_y.fract()),
+                WallSide::Vertical => ((1.0, 0.0), 1.0 - hit_y.fract()),
+                WallSide::Horizontal if step_y > 0 => ((0.0, -1.0), 1.0 - hit_x.fract()),
+                WallSide::Horizontal => ((0.0, 1.0), hit_x.fract()),
+            };
+
+            return Some(RayHit {
+                distance,
+                x: origin.0 + dir_x * distance,
+                y: origin.1 + dir_y * distance,
+                cell_x: tile_x,
+                cell_y: tile_y,
+                tile: map.tile(tile_x, tile_y).unwrap_or_default(),
+                side,
+                normal,
+                offset: offset.clamp(0.0, 1.0),
+            });
+        }
+
+        if side_x < side_y {
+            t = t_enter + side_x;
+            side_x += delta_x;
+            cell_x += step_x;
+            side = WallSide::Vertical;
+        } else {
+            t = t_enter + side_y;
+            side_y += delta_y;
+            cell_y += step_y;
+            side = WallSide::Horizontal;
+        }
+    }
+}
+
+/// Intersects a ray with the range `0..size` along one axis.
+///
+/// # Returns
+///
+/// The ray parameters where the ray enters and leaves the range, or `None` if a ray
+/// parallel to the axis never enters it.
+fn slab(pos: f32, dir: f32, size: f32) -> Option<(f32, f32)> {
+    if dir == 0.0 {
+        return (0.0..size)
+            .contains(&pos)
+            .then_some((f32::NEG_INFINITY, f32::INFINITY));
+    }
+
+    let t1 = -pos / dir;
+    let t2 = (size - pos) / dir;
+    Some((t1.min(t2), t1.max(t2)))
+}
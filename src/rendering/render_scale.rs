@@ -0,0 +1,119 @@
+//! # Render Scale Module
+//!
+//! This module provides [`RenderScale`], the resolution the 3D view is rendered at and how it
+//! is scaled up to the window.
+//!
+//! Rendering fewer columns than the window is wide casts fewer rays and shades fewer pixels,
+//! which helps on slow machines. The low resolution frame is scaled up with the nearest pixel,
+//! so it looks pixelated rather than blurry, e.g. for a retro look.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rust_ray::rendering::RenderScale;
+//!
+//! // 320 columns on a 1920x1080 window, every rendered pixel becomes a 6x6 block
+//! let mut scale = RenderScale::columns(320);
+//! assert_eq!(scale.resolution(1920, 1080), (320, 180));
+//! assert_eq!(scale.destination(1920, 1080), (0, 0, 1920, 1080));
+//!
+//! // On a 1366x768 window only whole multiples are used, with black bars around the view
+//! scale.integer_scaling = true;
+//! assert_eq!(scale.destination(1366, 768), (43, 24, 1280, 720));
+//! ```
+
+use std::*;
+
+/// The resolution the 3D view is rendered at and how it is scaled up to the window.
+///
+/// The default renders one column per window pixel, like [`render_3d`](super::render_3d).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RenderScale {
+    /// The number of columns, and so rays, the view is rendered with, `None` for one column
+    /// per window pixel. The number of rows follows the aspect ratio of the window.
+    pub columns: Option<u32>,
+    /// Scales the view up by whole multiples only and centers it with black bars around it,
+    /// so every rendered pixel covers the same number of window pixels.
+    pub integer_scaling: bool,
+}
+
+impl RenderScale {
+    /// Creates a render scale with one column per window pixel.
+    pub fn native() -> Self {
+        Self::default()
+    }
+
+    /// Creates a render scale with a fixed number of columns, stretched to the whole window.
+    ///
+    /// # Arguments
+    ///
+    /// * `columns` - The number of columns to render, at least 1.
+    pub fn columns(columns: u32) -> Self {
+        Self {
+            columns: Some(columns),
+            integer_scaling: false,
+        }
+    }
+
+    /// Returns the resolution the view is rendered at.
+    ///
+    /// The view is never rendered with more columns than the window is wide.
+    ///
+    /// # Arguments
+    ///
+    /// * `screen_width` - The width of the window.
+    /// * `screen_height` - The height of the window.
+    ///
+    /// # Returns
+    ///
+    /// The width and height of the rendered view in pixels.
+    pub fn resolution(&self, screen_width: i32, screen_height: i32) -> (i32, i32) {
+        let (screen_width, screen_height) = (screen_width.max(0), screen_height.max(0));
+        let width = match self.columns {
+            Some(columns) => i32::try_from(columns.max(1))
+                .unwrap_or(i32::MAX)
+                .min(screen_width),
+            None => screen_width,
+        };
+        if width == screen_width {
+            return (screen_width, screen_height);
+        }
+
+        let height = screen_height as f32 * width as f32 / screen_width as f32;
+        (width, (height.round() as i32).max(1))
+    }
+
+    /// Returns where the rendered view is drawn in the window.
+    ///
+    /// # Arguments
+    ///
+    /// * `screen_width` - The width of the window.
+    /// * `screen_height` - The height of the window.
+    ///
+    /// # Returns
+    ///
+    /// The x- and y-coordinate of the top-left corner and the width and height in window
+    /// pixels, the whole window unless [`integer_scaling`](Self::integer_scaling) is on.
+    pub fn destination(&self, screen_width: i32, screen_height: i32) -> (i32, i32, i32, i32) {
+        let (screen_width, screen_height) = (screen_width.max(0), screen_height.max(0));
+        let (width, height) = self.resolution(screen_width, screen_height);
+        if !self.integer_scaling || width == 0 || height == 0 {
+            return (0, 0, screen_width, screen_height);
+        }
+
+        let factor = (screen_width / width).min(screen_height / height).max(1);
+        let (width, height) = (width * factor, height * factor);
+        (
+            (screen_width - width) / 2,
+            (screen_height - height) / 2,
+            width,
+            height,
+        )
+    }
+
+    /// Returns true if the view is rendered at the size of the window and drawn over all of it.
+    pub fn is_native(&self, screen_width: i32, screen_height: i32) -> bool {
+        let (width, height) = self.resolution(screen_width, screen_height);
+        self.destination(screen_width, screen_height) == (0, 0, width, height)
+    }
+}
@@ -1,248 +1,419 @@
 //! # Renderer Module
 //!
 //! This module provides functionality for rendering 2D and 3D scenes using OpenGL.
+//!
+//! By default every pass is batched into one vertex buffer and drawn with a single draw call
+//! through a shader, which needs OpenGL 3.3. The walls, sprites and the 2D map look the same
+//! as in the [`software`](super::software) renderer.
+//!
+//! With the `fixed-function` feature, or when the driver doesn't support OpenGL 3.3, frames
+//! are rendered on the CPU by the software renderer instead and uploaded to the screen as a
+//! single image with the fixed-function pipeline.
 
-use crate::utilities::opengl::{clear_screen, setup_viewport};
+use crate::utilities::opengl::{clear_screen, draw_image_scaled, setup_viewport};
 use glu_sys::*;
-use std::f32::consts::PI;
 use std::*;
 
-use super::map::draw_map_2d;
-use super::player::draw_player;
-use crate::rrm::rrm_support::{MAP_CUBE_SIZE, MAP_DATA, MAP_HEIGHT, MAP_WIDTH};
+use super::camera::Camera;
+use super::depth_buffer::DepthBuffer;
+#[cfg(not(feature = "fixed-function"))]
+use super::gpu;
+use super::image::Image;
+use super::raycast::{raycast, RayHit};
+use super::render_scale::RenderScale;
+use super::software::{debug_render_image, render_2d_image, render_3d_image};
+use super::sprite::Sprite;
+use super::texture::Textures;
+use crate::rrm::Map;
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+use sdl2::pixels::Color;
 
 // Constants
-const FOV: f32 = PI / 3.0; // 60 degrees field of view
-const RAY_STEP: f32 = 0.1;
+const COLUMNS_PER_TASK: usize = 64; // Columns cast by one rayon task, to keep the overhead low
+
+/// Returns why the OpenGL 3.3 renderer can't be used with the current OpenGL context.
+///
+/// The renderer is created on the first frame rendered with a context, so call this after
+/// that frame, e.g. to log why the fixed-function fallback is used.
+///
+/// # Returns
+///
+/// The error, or `None` if the renderer works, no frame has been rendered with the context yet
+/// or the `fixed-function` feature is enabled.
+pub fn gpu_error() -> Option<String> {
+    #[cfg(not(feature = "fixed-function"))]
+    return gpu::error();
+
+    #[cfg(feature = "fixed-function")]
+    None
+}
+
+/// Renders with the OpenGL 3.3 renderer of an OpenGL context from now on.
+///
+/// # Arguments
+///
+/// * `id` - The id of the context, unique for the lifetime of the program.
+/// * `context` - The SDL handle of the context, which must be current on this thread.
+#[cfg_attr(feature = "fixed-function", allow(unused_variables))]
+pub(crate) fn bind_context(id: u64, context: sdl2::sys::SDL_GLContext) {
+    #[cfg(not(feature = "fixed-function"))]
+    gpu::bind_context(id, context);
+}
+
+/// Deletes the OpenGL 3.3 renderer of an OpenGL context before the context is destroyed.
+///
+/// # Arguments
+///
+/// * `id` - The id of the context.
+/// * `is_current` - Whether the context is current, so the OpenGL objects can be deleted.
+#[cfg_attr(feature = "fixed-function", allow(unused_variables))]
+pub(crate) fn release_context(id: u64, is_current: bool) {
+    #[cfg(not(feature = "fixed-function"))]
+    gpu::release_context(id, is_current);
+}
 
 /// Renders a 2D scene.
 ///
 /// # Arguments
 ///
-/// * `player_x` - The player's x-coordinate.
-/// * `player_y` - The player's y-coordinate.
-/// * `player_angle` - The player's viewing angle.
+/// * `map` - The map to render.
+/// * `camera` - The camera to render from.
 /// * `screen_width` - The width of the screen.
 /// * `screen_height` - The height of the screen.
-pub fn render_2d(
-    player_x: f32,
-    player_y: f32,
-    player_angle: f32,
-    screen_width: i32,
-    screen_height: i32,
-) {
-    unsafe {
-        // OpenGL
-        setup_viewport(screen_width, screen_height);
-        clear_screen();
-
-        // My own functions
-        draw_map_2d();
-        draw_player(player_x, player_y, player_angle);
+pub fn render_2d(map: &Map, camera: &Camera, screen_width: i32, screen_height: i32) {
+    #[cfg(not(feature = "fixed-function"))]
+    if gpu::with_renderer(|renderer| unsafe {
+        renderer.render_2d(map, camera, screen_width, screen_height)
+    })
+    .is_some()
+    {
+        return;
     }
+
+    let mut frame = new_frame(screen_width, screen_height);
+    render_2d_image(map, camera, &mut frame);
+
+    let destination = (0, 0, screen_width, screen_height);
+    unsafe { present(&frame, destination, screen_width, screen_height) };
 }
 
 /// Renders a 3D scene.
 ///
+/// See [`render_3d_image`] for how the scene is drawn.
+///
 /// # Arguments
 ///
-/// * `player_x` - The player's x-coordinate.
-/// * `player_y` - The player's y-coordinate.
-/// * `player_angle` - The player's viewing angle.
+/// * `map` - The map to render.
+/// * `textures` - The wall and floor textures and the sprite sheet of the map.
+/// * `sprites` - The billboard sprites to draw, e.g. from [`LevelEntities::sprites`](crate::entities::LevelEntities::sprites).
+/// * `camera` - The camera to render from.
 /// * `screen_width` - The width of the screen.
 /// * `screen_height` - The height of the screen.
+///
+/// # Returns
+///
+/// The depth buffer of the frame, for depth testing anything drawn on top against the walls.
 pub fn render_3d(
-    player_x: f32,
-    player_y: f32,
-    player_angle: f32,
+    map: &Map,
+    textures: &Textures,
+    sprites: &[Sprite],
+    camera: &Camera,
     screen_width: i32,
     screen_height: i32,
-) {
-    unsafe {
-        // OpenGL
-        setup_viewport(screen_width, screen_height);
-        clear_screen();
+) -> DepthBuffer {
+    #[cfg(not(feature = "fixed-function"))]
+    if let Some(depth) = gpu::with_renderer(|renderer| unsafe {
+        renderer.render_3d(map, textures, sprites, camera, screen_width, screen_height)
+    }) {
+        return depth;
+    }
 
-        // My own functions
-        let rays = draw_rays_3d(player_x, player_y, player_angle, screen_width);
-        render_3d_walls(
-            rays,
-            player_x,
-            player_y,
-            player_angle,
-            screen_width,
-            screen_height,
-        );
+    let mut frame = new_frame(screen_width, screen_height);
+    let depth = render_3d_image(map, textures, sprites, camera, &mut frame);
+
+    let destination = (0, 0, screen_width, screen_height);
+    unsafe { present(&frame, destination, screen_width, screen_height) };
+    depth
+}
+
+/// Renders a 3D scene at a lower resolution and scales it up to the screen.
+///
+/// The scene is rendered like with [`render_3d`], but with the columns and rows of the render
+/// scale, and scaled up with the nearest pixel. Casting fewer rays makes the 3D view cheaper
+/// and gives it a pixelated, retro look.
+///
+/// # Arguments
+///
+/// * `map` - The map to render.
+/// * `textures` - The wall and floor textures and the sprite sheet of the map.
+/// * `sprites` - The billboard sprites to draw.
+/// * `camera` - The camera to render from.
+/// * `scale` - The resolution to render at and how to scale it to the screen.
+/// * `screen_width` - The width of the screen.
+/// * `screen_height` - The height of the screen.
+///
+/// # Returns
+///
+/// The depth buffer of the frame, with one column per rendered column rather than per screen
+/// column.
+pub fn render_3d_scaled(
+    map: &Map,
+    textures: &Textures,
+    sprites: &[Sprite],
+    camera: &Camera,
+    scale: &RenderScale,
+    screen_width: i32,
+    screen_height: i32,
+) -> DepthBuffer {
+    if scale.is_native(screen_width, screen_height) {
+        return render_3d(map, textures, sprites, camera, screen_width, screen_height);
     }
+
+    let (width, height) = scale.resolution(screen_width, screen_height);
+    let destination = scale.destination(screen_width, screen_height);
+
+    #[cfg(not(feature = "fixed-function"))]
+    if let Some(depth) = gpu::with_renderer(|renderer| unsafe {
+        renderer.bind_target(width, height);
+        let depth = renderer.render_3d(map, textures, sprites, camera, width, height);
+        renderer.present_target(destination, screen_width, screen_height);
+        depth
+    }) {
+        return depth;
+    }
+
+    let mut frame = new_frame(width, height);
+    let depth = render_3d_image(map, textures, sprites, camera, &mut frame);
+
+    unsafe { present(&frame, destination, screen_width, screen_height) };
+    depth
 }
 
+/// Renders a 3D scene with the 2D map and the player drawn on top of it.
+///
+/// # Arguments
+///
+/// * `map` - The map to render.
+/// * `textures` - The wall and floor textures and the sprite sheet of the map.
+/// * `sprites` - The billboard sprites to draw, e.g. from [`LevelEntities::sprites`](crate::entities::LevelEntities::sprites).
+/// * `camera` - The camera to render from.
+/// * `screen_width` - The width of the screen.
+/// * `screen_height` - The height of the screen.
+///
+/// # Returns
+///
+/// The depth buffer of the 3D view.
 pub fn debug_render(
-    player_x: f32,
-    player_y: f32,
-    player_angle: f32,
+    map: &Map,
+    textures: &Textures,
+    sprites: &[Sprite],
+    camera: &Camera,
+    screen_width: i32,
+    screen_height: i32,
+) -> DepthBuffer {
+    #[cfg(not(feature = "fixed-function"))]
+    if let Some(depth) = gpu::with_renderer(|renderer| unsafe {
+        renderer.debug_render(map, textures, sprites, camera, screen_width, screen_height)
+    }) {
+        return depth;
+    }
+
+    let mut frame = new_frame(screen_width, screen_height);
+    let depth = debug_render_image(map, textures, sprites, camera, &mut frame);
+
+    let destination = (0, 0, screen_width, screen_height);
+    unsafe { present(&frame, destination, screen_width, screen_height) };
+    depth
+}
+
+/// Draws square markers on top of the current frame, e.g. the entities on the 2D map.
+///
+/// The markers are drawn in one batch. Call it after [`render_2d`] or [`debug_render`], so
+/// they end up on top of the frame.
+///
+/// # Arguments
+///
+/// * `points` - The centers of the markers in screen pixels.
+/// * `size` - The width and height of the markers in pixels.
+/// * `color` - The colour of the markers.
+/// * `screen_width` - The width of the screen.
+/// * `screen_height` - The height of the screen.
+pub fn draw_markers(
+    points: &[(f32, f32)],
+    size: f32,
+    color: Color,
     screen_width: i32,
     screen_height: i32,
 ) {
+    #[cfg(not(feature = "fixed-function"))]
+    if gpu::with_renderer(|renderer| unsafe {
+        renderer.draw_markers(points, size, color, screen_width, screen_height)
+    })
+    .is_some()
+    {
+        return;
+    }
+
     unsafe {
         // OpenGL
         setup_viewport(screen_width, screen_height);
-        clear_screen();
 
-        // My own functions
-        let rays = draw_rays_3d(player_x, player_y, player_angle, screen_width);
-        render_3d_walls(
-            rays,
-            player_x,
-            player_y,
-            player_angle,
-            screen_width,
-            screen_height,
-        );
-        draw_map_2d();
-        draw_player(player_x, player_y, player_angle);
+        glColor3ub(color.r, color.g, color.b);
+        glPointSize(size);
+        glBegin(GL_POINTS);
+        for &(x, y) in points {
+            glVertex2f(x, y);
+        }
+        glEnd();
     }
 }
 
-/// Casts rays for 3D rendering.
-///
-/// # Safety
+/// A wall as seen in one screen column.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WallColumn {
+    /// Where the ray of the column hit the wall.
+    pub hit: RayHit,
+    /// The perpendicular distance of the wall from the camera plane in world units.
+    pub distance: f32,
+    /// The top edge of the wall in screen rows.
+    pub top: f32,
+    /// The bottom edge of the wall in screen rows.
+    pub bottom: f32,
+}
+
+impl WallColumn {
+    /// Returns the height of the wall in screen rows.
+    pub fn height(&self) -> f32 {
+        self.bottom - self.top
+    }
+}
+
+/// Casts one ray per screen column and projects the walls they hit onto the screen.
 ///
-/// This function uses unsafe code and should be used carefully.
+/// The distance of a wall is measured perpendicular to the camera plane rather than along the
+/// ray, so flat walls stay flat instead of bulging towards the middle of the screen. The
+/// columns are cast in parallel, see [`cast_walls_into`].
 ///
 /// # Arguments
 ///
-/// * `player_x` - The player's x-coordinate.
-/// * `player_y` - The player's y-coordinate.
-/// * `player_angle` - The player's viewing angle.
+/// * `map` - The map to cast the rays against.
+/// * `camera` - The camera to render from.
 /// * `screen_width` - The width of the screen.
+/// * `screen_height` - The height of the screen.
 ///
 /// # Returns
 ///
-/// A vector of (x, y) coordinates representing the endpoints of the cast rays.
-unsafe fn draw_rays_3d(
-    player_x: f32,
-    player_y: f32,
-    player_angle: f32,
+/// One wall per screen column, `None` for rays that leave the map without hitting a wall.
+pub fn cast_walls(
+    map: &Map,
+    camera: &Camera,
     screen_width: i32,
-) -> Vec<(f32, f32)> {
-    let num_rays: usize = screen_width as usize;
-    let angle_increment = FOV / num_rays as f32;
-    let mut rays = Vec::with_capacity(num_rays);
-
-    for i in 0..num_rays {
-        let ray_angle = player_angle - (FOV / 2.0) + (i as f32 * angle_increment);
-        let (ray_x, ray_y) = cast_ray(player_x, player_y, ray_angle);
-        rays.push((ray_x, ray_y));
-    }
-
-    rays
+    screen_height: i32,
+) -> Vec<Option<WallColumn>> {
+    let mut walls = vec![None; screen_width.max(0) as usize];
+    cast_walls_into(map, camera, screen_height, &mut walls);
+    walls
 }
 
-/// Casts a single ray.
+/// Casts one ray per screen column into a preallocated buffer, in parallel.
 ///
-/// # Safety
+/// Every column is cast on its own with [`cast_wall`], so the result is the same as casting
+/// the columns one after another. Reusing the buffer from frame to frame avoids allocating
+/// it every frame.
 ///
-/// This function uses unsafe code and should be used carefully.
+/// # Arguments
+///
+/// * `map` - The map to cast the rays against.
+/// * `camera` - The camera to render from.
+/// * `screen_height` - The height of the screen.
+/// * `walls` - The buffer to fill, with one entry per screen column.
+pub fn cast_walls_into(
+    map: &Map,
+    camera: &Camera,
+    screen_height: i32,
+    walls: &mut [Option<WallColumn>],
+) {
+    let num_rays = walls.len();
+
+    walls
+        .par_iter_mut()
+        .with_min_len(COLUMNS_PER_TASK)
+        .enumerate()
+        .for_each(|(i, wall)| {
+            *wall = cast_wall(map, camera, i, num_rays, screen_height);
+        });
+}
+
+/// Casts the ray of a single screen column and projects the wall it hits onto the screen.
 ///
 /// # Arguments
 ///
-/// * `player_x` - The player's x-coordinate.
-/// * `player_y` - The player's y-coordinate.
-/// * `ray_angle` - The angle of the ray.
+/// * `map` - The map to cast the ray against.
+/// * `camera` - The camera to render from.
+/// * `column` - The screen column.
+/// * `columns` - The number of screen columns.
+/// * `screen_height` - The height of the screen.
 ///
 /// # Returns
 ///
-/// The (x, y) coordinates of where the ray hits a wall.
-unsafe fn cast_ray(player_x: f32, player_y: f32, ray_angle: f32) -> (f32, f32) {
-    let delta_x = ray_angle.cos();
-    let delta_y = ray_angle.sin();
-    let mut ray_x = player_x;
-    let mut ray_y = player_y;
-
-    loop {
-        ray_x += delta_x * RAY_STEP;
-        ray_y += delta_y * RAY_STEP;
+/// The wall seen in the column, `None` if the ray leaves the map without hitting a wall.
+pub fn cast_wall(
+    map: &Map,
+    camera: &Camera,
+    column: usize,
+    columns: usize,
+    screen_height: i32,
+) -> Option<WallColumn> {
+    let ray_angle = camera.ray_angle(column, columns);
+    let hit = raycast(map, camera.position(), ray_angle, f32::INFINITY)?;
+    let distance = hit.distance * (ray_angle - camera.angle).cos();
 
-        let map_x = ray_x as usize / MAP_CUBE_SIZE as usize;
-        let map_y = ray_y as usize / MAP_CUBE_SIZE as usize;
+    // A wall one cube away fills the whole screen height
+    let height =
+        (screen_height as f32 / distance.max(camera.clamped_near_clip())) * map.cube_size();
+    let horizon = camera.horizon(screen_height);
 
-        if map_x >= MAP_WIDTH || map_y >= MAP_HEIGHT || MAP_DATA[map_y][map_x] == 1 {
-            break;
-        }
-    }
+    Some(WallColumn {
+        hit,
+        distance,
+        top: horizon - (height / 2.0),
+        bottom: horizon + (height / 2.0),
+    })
+}
 
-    (ray_x, ray_y)
+/// Creates a black frame the size of the screen.
+fn new_frame(screen_width: i32, screen_height: i32) -> Image {
+    Image::filled(
+        screen_width.max(0) as u32,
+        screen_height.max(0) as u32,
+        Color::RGB(0, 0, 0),
+    )
 }
 
-/// Renders 3D walls based on ray casting results.
-///
-/// # Safety
-///
-/// This function uses unsafe OpenGL calls and should be used carefully.
+/// Clears the screen and uploads a frame to it, stretched over a rectangle of the screen.
 ///
 /// # Arguments
 ///
-/// * `rays` - A vector of ray endpoints.
-/// * `player_x` - The player's x-coordinate.
-/// * `player_y` - The player's y-coordinate.
-/// * `player_angle` - The player's viewing angle.
+/// * `frame` - The frame to upload.
+/// * `destination` - The x- and y-coordinate, width and height of the rectangle.
 /// * `screen_width` - The width of the screen.
 /// * `screen_height` - The height of the screen.
-unsafe fn render_3d_walls(
-    rays: Vec<(f32, f32)>,
-    player_x: f32,
-    player_y: f32,
-    player_angle: f32,
+///
+/// # Safety
+///
+/// This function uses unsafe OpenGL calls and should be used carefully.
+unsafe fn present(
+    frame: &Image,
+    destination: (i32, i32, i32, i32),
     screen_width: i32,
     screen_height: i32,
 ) {
-    let num_rays: usize = screen_width as usize;
-    let slice_width = screen_width as f32 / num_rays as f32;
-
-    for (i, (ray_x, ray_y)) in rays.iter().enumerate() {
-        let ray_angle = player_angle - (FOV / 2.0) + (i as f32 * FOV / num_rays as f32);
-        let distance = ((ray_x - player_x).powi(2) + (ray_y - player_y).powi(2)).sqrt();
-        let perpendicular_distance: f32 = distance * (ray_angle - player_angle).cos();
-
-        // Calculate wall height
-        let wall_height: f32 = (screen_height as f32 / perpendicular_distance) * MAP_CUBE_SIZE;
-
-        unsafe {
-            glBegin(GL_QUADS);
-            glColor3f(0.0, 1.0, 0.0); // Or use the wall texture color here
-
-            let start_x = i as f32 * slice_width;
-            let end_x = start_x + slice_width;
-
-            glTexCoord2f(start_x / screen_width as f32, 0.5);
-            glVertex3f(
-                start_x,
-                (screen_height as f32 / 2.0) - (wall_height / 2.0),
-                0.0,
-            );
-
-            glTexCoord2f(end_x / screen_width as f32, 0.5);
-            glVertex3f(
-                end_x,
-                (screen_height as f32 / 2.0) - (wall_height / 2.0),
-                0.0,
-            );
-
-            glTexCoord2f(end_x / screen_width as f32, 1.0);
-            glVertex3f(
-                end_x,
-                (screen_height as f32 / 2.0) + (wall_height / 2.0),
-                0.0,
-            );
-
-            glTexCoord2f(start_x / screen_width as f32, 1.0);
-            glVertex3f(
-                start_x,
-                (screen_height as f32 / 2.0) + (wall_height / 2.0),
-                0.0,
-            );
-
-            glEnd();
-        }
+    unsafe {
+        // OpenGL
+        setup_viewport(screen_width, screen_height);
+        clear_screen();
+
+        let (x, y, width, height) = destination;
+        draw_image_scaled(frame, x, y, width, height);
     }
 }
@@ -0,0 +1,451 @@
+//! # Software Module
+//!
+//! This module provides the software renderer, a CPU rasterizer that renders the 2D and 3D
+//! views into an [`Image`]. It doesn't need an OpenGL context, so frames can be rendered
+//! headless, e.g. for screenshots or tests on machines without a GPU. The OpenGL functions
+//! of the [`renderer`](super::renderer) module render with it and upload the finished frame.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rust_ray::rendering::{render_3d_image, Camera, Image, Textures};
+//! use rust_ray::Map;
+//! use sdl2::pixels::Color;
+//!
+//! let map = Map::default();
+//! let camera = Camera::new(1.5 * map.cube_size(), 1.5 * map.cube_size(), 0.0);
+//!
+//! let mut frame = Image::filled(320, 200, Color::RGB(0, 0, 0));
+//! let depth = render_3d_image(&map, &Textures::new(), &[], &camera, &mut frame);
+//! assert_eq!(depth.len(), 320);
+//! ```
+
+use super::camera::Camera;
+use super::depth_buffer::DepthBuffer;
+use super::image::Image;
+use super::map::draw_map_2d;
+use super::player::draw_player;
+use super::raycast::WallSide;
+use super::renderer::{cast_walls, WallColumn};
+use super::sprite::Sprite;
+use super::texture::Textures;
+use crate::rrm::{Map, WallAppearance};
+use sdl2::pixels::Color;
+use std::ops::Range;
+use std::*;
+
+// Constants
+const BACKGROUND_COLOR: Color = Color::RGB(0, 0, 0);
+const DEFAULT_CEILING_COLOR: Color = Color::RGB(0, 0, 0);
+const DEFAULT_FLOOR_COLOR: Color = Color::RGB(0, 0, 0);
+const HORIZONTAL_SIDE_BRIGHTNESS: f32 = 0.7; // Walls hit on a horizontal grid line are darker
+const SPRITE_ALPHA_THRESHOLD: u8 = 128; // Sprite pixels with less alpha are transparent
+
+/// Renders a 2D scene into an image.
+///
+/// # Arguments
+///
+/// * `map` - The map to render.
+/// * `camera` - The camera to render from.
+/// * `frame` - The image to render into, the whole image is drawn over.
+pub fn render_2d_image(map: &Map, camera: &Camera, frame: &mut Image) {
+    frame.fill(BACKGROUND_COLOR);
+    draw_map_2d(frame, map);
+    draw_player(frame, camera.x, camera.y, camera.angle);
+}
+
+/// Renders a 3D scene into an image.
+///
+/// Walls are drawn with the texture of their tile if it has one, otherwise in the wall
+/// colour from the map palette. The ceiling and the floor are drawn in the colours of the
+/// map, floors of tiles with their own floor colour or texture are cast per pixel. Sprites
+/// are drawn last and hidden behind walls that are closer to the camera.
+///
+/// # Arguments
+///
+/// * `map` - The map to render.
+/// * `textures` - The wall and floor textures and the sprite sheet of the map.
+/// * `sprites` - The billboard sprites to draw, e.g. from [`LevelEntities::sprites`](crate::entities::LevelEntities::sprites).
+/// * `camera` - The camera to render from.
+/// * `frame` - The image to render into, the whole image is drawn over.
+///
+/// # Returns
+///
+/// The depth buffer of the frame, for depth testing anything drawn on top against the walls.
+pub fn render_3d_image(
+    map: &Map,
+    textures: &Textures,
+    sprites: &[Sprite],
+    camera: &Camera,
+    frame: &mut Image,
+) -> DepthBuffer {
+    draw_floor_and_ceiling(map, textures, camera, frame);
+    let walls = cast_walls(map, camera, frame.width as i32, frame.height as i32);
+    let depth = draw_walls(map, textures, &walls, frame);
+    draw_sprites(map, textures, sprites, &depth, camera, frame);
+
+    depth
+}
+
+/// Renders a 3D scene into an image with the 2D map and the player drawn on top of it.
+///
+/// # Arguments
+///
+/// * `map` - The map to render.
+/// * `textures` - The wall and floor textures and the sprite sheet of the map.
+/// * `sprites` - The billboard sprites to draw.
+/// * `camera` - The camera to render from.
+/// * `frame` - The image to render into, the whole image is drawn over.
+///
+/// # Returns
+///
+/// The depth buffer of the 3D view.
+pub fn debug_render_image(
+    map: &Map,
+    textures: &Textures,
+    sprites: &[Sprite],
+    camera: &Camera,
+    frame: &mut Image,
+) -> DepthBuffer {
+    let depth = render_3d_image(map, textures, sprites, camera, frame);
+    draw_map_2d(frame, map);
+    draw_player(frame, camera.x, camera.y, camera.angle);
+
+    depth
+}
+
+/// Draws the walls seen in every screen column.
+///
+/// Walls hit on a horizontal grid line are drawn darker than walls hit on a vertical one, so
+/// corners are easy to read, and walls fade into the fog of the map with distance.
+///
+/// # Arguments
+///
+/// * `map` - The map the rays were cast against.
+/// * `textures` - The wall and floor textures of the map.
+/// * `walls` - The wall of every screen column, from [`cast_walls`].
+/// * `frame` - The image to draw into.
+///
+/// # Returns
+///
+/// The depth buffer with the perpendicular distance of the wall in every screen column.
+fn draw_walls(
+    map: &Map,
+    textures: &Textures,
+    walls: &[Option<WallColumn>],
+    frame: &mut Image,
+) -> DepthBuffer {
+    let fog = map.metadata().fog;
+    let mut depth = DepthBuffer::new(walls.len());
+
+    for (column, wall) in walls.iter().enumerate() {
+        // Rays that left the map don't hit a wall
+        let Some(wall) = wall else {
+            continue;
+        };
+        depth.set(column, wall.distance);
+
+        let brightness = side_brightness(wall.hit.side);
+        let fog_factor = fog.map_or(0.0, |fog| fog.factor(wall.distance / map.cube_size()));
+        let shade = |color| shade_color(color, brightness, fog.map(|fog| fog.color), fog_factor);
+
+        // Sample a one pixel wide strip of the wall texture at the exact hit offset,
+        // or colour the wall with the tile type the ray hit if it has no texture
+        let texture = textures.wall(wall.hit.tile);
+        let wall_color = map
+            .tile_type(wall.hit.tile)
+            .map_or(Color::RGB(0, 255, 0), |tile_type| tile_type.wall_color());
+        let wall_color = shade(wall_color);
+
+        for y in pixel_rows(wall.top, wall.bottom, frame.height) {
+            let color = match texture {
+                Some(image) => {
+                    let v = (y as f32 + 0.5 - wall.top) / wall.height();
+                    shade(image.sample(wall.hit.offset, v))
+                }
+                None => wall_color,
+            };
+            frame.set_pixel(column as u32, y, color);
+        }
+    }
+
+    depth
+}
+
+/// Draws the billboard sprites on top of the walls.
+///
+/// Sprites are one cube high, stand on the floor and always face the camera. They are drawn
+/// from the furthest to the nearest, one screen column at a time, and every column is skipped
+/// where a wall is closer to the camera than the sprite.
+///
+/// # Arguments
+///
+/// * `map` - The map the sprites are on.
+/// * `textures` - The textures of the map, holding the sprite sheet.
+/// * `sprites` - The sprites to draw.
+/// * `depth` - The depth buffer of the wall pass.
+/// * `camera` - The camera to render from.
+/// * `frame` - The image to draw into.
+fn draw_sprites(
+    map: &Map,
+    textures: &Textures,
+    sprites: &[Sprite],
+    depth: &DepthBuffer,
+    camera: &Camera,
+    frame: &mut Image,
+) {
+    let Some(sprite_sheet) = textures.sprite_sheet() else {
+        return;
+    };
+
+    let mut projected = sprites
+        .iter()
+        .filter(|sprite| sprite.sprite_id < sprite_sheet.len())
+        .filter_map(|sprite| {
+            let projection =
+                project_sprite(sprite, map.cube_size(), camera, frame.width, frame.height)?;
+            Some((projection, sprite.sprite_id))
+        })
+        .collect::<Vec<_>>();
+
+    // Draw the furthest sprites first, so nearer ones cover them
+    projected.sort_by(|(a, _), (b, _)| b.distance.total_cmp(&a.distance));
+
+    let fog = map.metadata().fog;
+    let aspect = sprite_sheet.sprite_width as f32 / sprite_sheet.sprite_height as f32;
+
+    for (projection, sprite_id) in projected {
+        let fog_factor = fog.map_or(0.0, |fog| fog.factor(projection.distance / map.cube_size()));
+        let height = projection.bottom - projection.top;
+
+        let half_width = height * aspect / 2.0;
+        let sprite_left = projection.center - half_width;
+        let sprite_right = projection.center + half_width;
+        let first = sprite_left.floor().max(0.0) as usize;
+        let last = (sprite_right.ceil().max(0.0) as usize).min(depth.len());
+
+        for column in first..last {
+            if !depth.is_visible(column, projection.distance) {
+                continue;
+            }
+
+            // Sample a one pixel wide strip of the sprite at this column
+            let u = (column as f32 + 0.5 - sprite_left) / (sprite_right - sprite_left);
+            for y in pixel_rows(projection.top, projection.bottom, frame.height) {
+                let v = (y as f32 + 0.5 - projection.top) / height;
+                let Some(color) = sprite_sheet.sample(sprite_id, u, v) else {
+                    continue;
+                };
+                if color.a < SPRITE_ALPHA_THRESHOLD {
+                    continue;
+                }
+
+                let color = shade_color(color, 1.0, fog.map(|fog| fog.color), fog_factor);
+                frame.set_pixel(column as u32, y, color);
+            }
+        }
+    }
+}
+
+/// Where a sprite appears on the screen.
+pub(crate) struct SpriteProjection {
+    /// The perpendicular distance of the sprite from the camera in world units.
+    pub(crate) distance: f32,
+    /// The center of the sprite in screen columns.
+    pub(crate) center: f32,
+    /// The top edge of the sprite in screen rows.
+    pub(crate) top: f32,
+    /// The bottom edge of the sprite in screen rows.
+    pub(crate) bottom: f32,
+}
+
+/// Projects a sprite onto the screen.
+///
+/// The sprite is centered on the column whose ray points at it, see [`Camera::project`], and
+/// is as high as a wall at the same distance.
+///
+/// # Returns
+///
+/// Where the sprite appears on the screen, or `None` if it is behind the camera or closer than
+/// its near clip distance.
+pub(crate) fn project_sprite(
+    sprite: &Sprite,
+    cube_size: f32,
+    camera: &Camera,
+    screen_width: u32,
+    screen_height: u32,
+) -> Option<SpriteProjection> {
+    let (center, distance) = camera.project(sprite.x, sprite.y, screen_width as usize)?;
+    if distance < camera.clamped_near_clip() {
+        return None;
+    }
+
+    let screen_height = screen_height as i32;
+    let size = (screen_height as f32 / distance) * cube_size;
+    let bottom = camera.horizon(screen_height) + (size / 2.0);
+
+    Some(SpriteProjection {
+        distance,
+        center,
+        top: bottom - size,
+        bottom,
+    })
+}
+
+/// Draws the ceiling above the horizon and the floor below it.
+///
+/// The ceiling is drawn in the ceiling colour of the map, one row at a time so it fades into
+/// the fog with distance. The floor is drawn the same way in the floor colour of the map,
+/// unless a tile in the map palette has its own floor. Then every floor pixel is traced back
+/// to the point on the floor it shows, and coloured with the floor of the tile at that point:
+/// its floor texture, its floor colour or the floor colour of the map.
+///
+/// # Arguments
+///
+/// * `map` - The map to render.
+/// * `textures` - The wall and floor textures of the map.
+/// * `camera` - The camera to render from.
+/// * `frame` - The image to draw into.
+pub(crate) fn draw_floor_and_ceiling(
+    map: &Map,
+    textures: &Textures,
+    camera: &Camera,
+    frame: &mut Image,
+) {
+    let metadata = map.metadata();
+    let floor_color = metadata.floor_color.unwrap_or(DEFAULT_FLOOR_COLOR);
+    let fog_color = metadata.fog.map(|fog| fog.color);
+    let cube_size = map.cube_size();
+    let screen_height = frame.height as i32;
+    let horizon = camera.horizon(screen_height);
+    let first_floor_row = pixel_rows(horizon, f32::INFINITY, frame.height).start;
+    let cast_floor = casts_floor(map);
+
+    // The floor point of a column's ray in world units per tile of row distance. The rays
+    // are evenly spaced on the camera plane, so the points step linearly along every row.
+    let rays = if cast_floor {
+        (0..frame.width as usize)
+            .map(|column| {
+                let ray_angle = camera.ray_angle(column, frame.width as usize);
+                let (sin, cos) = ray_angle.sin_cos();
+                let length = cube_size / (ray_angle - camera.angle).cos();
+                (cos * length, sin * length)
+            })
+            .collect::<Vec<_>>()
+    } else {
+        Vec::new()
+    };
+
+    for y in 0..frame.height {
+        if y < first_floor_row || !cast_floor {
+            let color = flat_row_color(map, camera, y, frame.height);
+            frame.fill_rect(0, y as i32, frame.width as i32, 1, color);
+            continue;
+        }
+
+        let distance = row_distance(y, horizon, screen_height);
+        let fog_factor = metadata.fog.map_or(0.0, |fog| fog.factor(distance));
+        for (column, &(ray_x, ray_y)) in rays.iter().enumerate() {
+            // Follow the ray of this column until it reaches the distance of the row
+            let world_x = camera.x + ray_x * distance;
+            let world_y = camera.y + ray_y * distance;
+
+            let tile_floor = map
+                .tile_at(world_x, world_y)
+                .and_then(|tile| map.tile_type(tile))
+                .and_then(|tile_type| Some((tile_type.id, tile_type.floor.as_ref()?)));
+            let color = match tile_floor {
+                Some((id, WallAppearance::Texture(_))) => match textures.floor(id) {
+                    Some(image) => image.sample(world_x / cube_size, world_y / cube_size),
+                    None => floor_color,
+                },
+                Some((_, WallAppearance::Color(color))) => *color,
+                None => floor_color,
+            };
+
+            let color = shade_color(color, 1.0, fog_color, fog_factor);
+            frame.set_pixel(column as u32, y, color);
+        }
+    }
+}
+
+/// Returns whether a tile in the map palette has its own floor, so the floor has to be
+/// cast per pixel.
+pub(crate) fn casts_floor(map: &Map) -> bool {
+    map.palette().iter().any(|tile| tile.floor.is_some())
+}
+
+/// Returns the colour of a screen row of the ceiling, or of the floor in the floor colour of
+/// the map, faded into the fog of the map.
+///
+/// # Arguments
+///
+/// * `map` - The map to render.
+/// * `camera` - The camera to render from.
+/// * `y` - The screen row.
+/// * `screen_height` - The height of the screen.
+pub(crate) fn flat_row_color(map: &Map, camera: &Camera, y: u32, screen_height: u32) -> Color {
+    let metadata = map.metadata();
+    let horizon = camera.horizon(screen_height as i32);
+    let color = if y < pixel_rows(horizon, f32::INFINITY, screen_height).start {
+        metadata.ceiling_color.unwrap_or(DEFAULT_CEILING_COLOR)
+    } else {
+        metadata.floor_color.unwrap_or(DEFAULT_FLOOR_COLOR)
+    };
+
+    let distance = row_distance(y, horizon, screen_height as i32);
+    let fog_factor = metadata.fog.map_or(0.0, |fog| fog.factor(distance));
+    shade_color(color, 1.0, metadata.fog.map(|fog| fog.color), fog_factor)
+}
+
+/// Returns the distance in tiles of the floor or ceiling shown by a screen row.
+///
+/// A wall one cube away fills the whole screen height, so the row at the bottom edge of the
+/// screen shows the floor one cube away when the camera looks straight ahead, and rows closer
+/// to the horizon show it further away.
+fn row_distance(y: u32, horizon: f32, screen_height: i32) -> f32 {
+    let from_horizon = (y as f32 + 0.5 - horizon).abs();
+    screen_height as f32 / (2.0 * from_horizon)
+}
+
+/// Returns the screen rows covered by a span from `top` to `bottom`.
+///
+/// A row is covered if its center lies inside the span, like OpenGL rasterizes polygons.
+fn pixel_rows(top: f32, bottom: f32, screen_height: u32) -> Range<u32> {
+    let row = |edge: f32| (edge - 0.5).ceil().clamp(0.0, screen_height as f32) as u32;
+    row(top)..row(bottom)
+}
+
+/// Returns how bright a wall is drawn depending on the kind of grid line the ray crossed.
+pub(crate) fn side_brightness(side: WallSide) -> f32 {
+    match side {
+        WallSide::Vertical => 1.0,
+        WallSide::Horizontal => HORIZONTAL_SIDE_BRIGHTNESS,
+    }
+}
+
+/// Darkens a colour and blends it into the fog colour.
+///
+/// # Arguments
+///
+/// * `color` - The colour to shade.
+/// * `brightness` - How bright the colour is drawn, from 0 (black) to 1 (unchanged).
+/// * `fog_color` - The colour of the fog, `None` if there is no fog.
+/// * `fog_factor` - How much of the fog colour covers the colour, from 0 to 1.
+///
+/// # Returns
+///
+/// The shaded colour, fully opaque.
+fn shade_color(color: Color, brightness: f32, fog_color: Option<Color>, fog_factor: f32) -> Color {
+    let fog_color = fog_color.unwrap_or(Color::RGB(0, 0, 0));
+    let channel = |value: u8, fog: u8| {
+        let shaded = value as f32 * brightness;
+        (shaded + (fog as f32 - shaded) * fog_factor).round() as u8
+    };
+
+    Color::RGB(
+        channel(color.r, fog_color.r),
+        channel(color.g, fog_color.g),
+        channel(color.b, fog_color.b),
+    )
+}
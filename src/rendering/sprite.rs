@@ -0,0 +1,148 @@
+//! # Sprite Module
+//!
+//! This module provides the billboard sprites drawn in the 3D view and the [`SpriteSheet`]
+//! they are looked up in.
+//!
+//! A sprite sheet is a single image divided into a grid of equally sized cells. Sprite ids
+//! count the cells row by row from the top-left corner, so in a sheet with 4 columns sprite 5
+//! is the second cell of the second row.
+
+use super::image::Image;
+use sdl2::pixels::Color;
+use std::path::Path;
+use std::*;
+
+/// The colour that is transparent in sprite sheets loaded from files without an alpha channel.
+pub const SPRITE_COLOR_KEY: Color = Color::RGB(255, 0, 255);
+
+/// A billboard sprite placed in the world, always facing the camera.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprite {
+    /// The x-coordinate of the sprite's center in world units.
+    pub x: f32,
+    /// The y-coordinate of the sprite's center in world units.
+    pub y: f32,
+    /// The cell of the sprite sheet the sprite is drawn with.
+    pub sprite_id: u32,
+}
+
+/// An image divided into a grid of equally sized sprites.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpriteSheet {
+    /// The image holding all sprites.
+    pub image: Image,
+    /// The width of a single sprite in pixels.
+    pub sprite_width: u32,
+    /// The height of a single sprite in pixels.
+    pub sprite_height: u32,
+}
+
+impl SpriteSheet {
+    /// Creates a sprite sheet from an image.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sprite size is 0.
+    pub fn new(image: Image, sprite_width: u32, sprite_height: u32) -> Self {
+        assert!(
+            sprite_width > 0 && sprite_height > 0,
+            "sprites must be at least one pixel wide and high"
+        );
+
+        Self {
+            image,
+            sprite_width,
+            sprite_height,
+        }
+    }
+
+    /// Loads a sprite sheet from a BMP file.
+    ///
+    /// Pixels in [`SPRITE_COLOR_KEY`] are made transparent, since BMP files usually don't
+    /// have an alpha channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the image file.
+    /// * `sprite_width` - The width of a single sprite in pixels.
+    /// * `sprite_height` - The height of a single sprite in pixels.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<SpriteSheet, String>` - The sprite sheet, or an error if the file can't be read.
+    pub fn load(path: &Path, sprite_width: u32, sprite_height: u32) -> Result<Self, String> {
+        let mut image = Image::load(path)?;
+
+        let key = [SPRITE_COLOR_KEY.r, SPRITE_COLOR_KEY.g, SPRITE_COLOR_KEY.b];
+        for pixel in image.pixels.chunks_mut(4) {
+            if pixel[..3] == key {
+                pixel[3] = 0;
+            }
+        }
+
+        Ok(Self::new(image, sprite_width, sprite_height))
+    }
+
+    /// Returns the number of sprite columns in the sheet.
+    pub fn columns(&self) -> u32 {
+        self.image.width / self.sprite_width
+    }
+
+    /// Returns the number of sprites in the sheet.
+    pub fn len(&self) -> u32 {
+        self.columns() * (self.image.height / self.sprite_height)
+    }
+
+    /// Returns true if the sheet is too small to hold a single sprite.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the cell of a sprite as texture coordinates.
+    ///
+    /// # Returns
+    ///
+    /// The left, top, right and bottom edge of the cell from 0 to 1, or `None` if the sheet
+    /// has no sprite with this id.
+    pub fn texture_rect(&self, sprite_id: u32) -> Option<(f32, f32, f32, f32)> {
+        if sprite_id >= self.len() {
+            return None;
+        }
+
+        let x = (sprite_id % self.columns()) * self.sprite_width;
+        let y = (sprite_id / self.columns()) * self.sprite_height;
+        let width = self.image.width as f32;
+        let height = self.image.height as f32;
+
+        Some((
+            x as f32 / width,
+            y as f32 / height,
+            (x + self.sprite_width) as f32 / width,
+            (y + self.sprite_height) as f32 / height,
+        ))
+    }
+
+    /// Samples a sprite with texture coordinates relative to its cell, using the nearest pixel.
+    ///
+    /// # Arguments
+    ///
+    /// * `sprite_id` - The sprite to sample.
+    /// * `u` - The horizontal coordinate, 0 is the left edge and 1 the right edge of the sprite.
+    /// * `v` - The vertical coordinate, 0 is the top edge and 1 the bottom edge of the sprite.
+    ///
+    /// # Returns
+    ///
+    /// The colour of the pixel, or `None` if the sheet has no sprite with this id.
+    pub fn sample(&self, sprite_id: u32, u: f32, v: f32) -> Option<Color> {
+        if sprite_id >= self.len() {
+            return None;
+        }
+
+        let x = (u.clamp(0.0, 1.0) * self.sprite_width as f32) as u32;
+        let y = (v.clamp(0.0, 1.0) * self.sprite_height as f32) as u32;
+        self.image.pixel(
+            (sprite_id % self.columns()) * self.sprite_width + x.min(self.sprite_width - 1),
+            (sprite_id / self.columns()) * self.sprite_height + y.min(self.sprite_height - 1),
+        )
+    }
+}
@@ -17,9 +17,11 @@ use std::io::Read;
 ///
 /// # Examples
 ///
-/// ```
+/// ```no_run
+/// use rust_ray::rendering::TextRenderer;
 /// use sdl2::pixels::Color;
 ///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
 /// let renderer = TextRenderer::new("assets/fonts/arial.ttf", Color::RGB(255, 255, 255))?;
 ///
 /// // Render text at specific coordinates
@@ -27,6 +29,8 @@ use std::io::Read;
 ///
 /// // Render centered text
 /// renderer.render_centered_text(800.0, 100.0, "Centered Text", 24.0);
+/// # Ok(())
+/// # }
 /// ```
 pub struct TextRenderer {
     /// The loaded font used for rendering
@@ -157,8 +161,14 @@ impl TextRenderer {
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```no_run
+    /// # use rust_ray::rendering::TextRenderer;
+    /// # use sdl2::pixels::Color;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let renderer = TextRenderer::new("assets/fonts/arial.ttf", Color::RGB(255, 255, 255))?;
     /// renderer.render_centered_text(800.0, 100.0, "Game Over", 32.0);
+    /// # Ok(())
+    /// # }
     /// ```
     pub fn render_centered_text(&self, screen_width: f32, y: f32, text: &str, scale: f32) {
         let text_width = self.calculate_text_width(text, scale);
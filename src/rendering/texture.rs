@@ -0,0 +1,135 @@
+//! # Texture Module
+//!
+//! This module provides [`Textures`], the wall and floor textures of a map keyed by tile id
+//! and the sprite sheet for billboard sprites.
+//!
+//! Tiles whose wall or floor appearance in the map palette is a texture path
+//! (`TILE=2,brick,solid,textures/brick.bmp,#B04020`) are loaded with [`Textures::load`].
+//! Paths are relative to the current working directory, like the map folder. Images are kept
+//! in memory and sampled by the software renderer, so no OpenGL context is needed. The OpenGL
+//! renderer uploads them itself the first time they are drawn and again after they change.
+
+use super::image::Image;
+use super::sprite::SpriteSheet;
+use crate::rrm::{Map, WallAppearance};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::*;
+
+/// The last revision handed out to a set of textures, see [`Textures::revision`].
+static LAST_REVISION: AtomicU64 = AtomicU64::new(0);
+
+/// The wall and floor textures of a map, keyed by tile id, and the sprite sheet.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use rust_ray::rendering::{render_3d, Camera, Textures};
+/// use rust_ray::{load_map, SdlWindow};
+///
+/// # fn main() -> Result<(), String> {
+/// let window = SdlWindow::new("Textured", 1280, 720)?;
+/// let map = load_map("level_1").map_err(|e| e.to_string())?;
+/// let textures = Textures::load(&map)?;
+///
+/// render_3d(&map, &textures, &[], &Camera::new(96.0, 96.0, 0.0), 1280, 720);
+/// window.swap_window();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Textures {
+    walls: HashMap<u8, Image>,
+    floors: HashMap<u8, Image>,
+    sprite_sheet: Option<SpriteSheet>,
+    revision: u64,
+}
+
+impl Textures {
+    /// Creates an empty set of textures, every wall and floor is drawn in its colour.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the wall and floor textures of every tile in the map palette that has one.
+    ///
+    /// # Arguments
+    ///
+    /// * `map` - The map whose palette names the textures.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Textures, String>` - The textures, or an error naming the tile whose
+    ///   texture can't be loaded.
+    pub fn load(map: &Map) -> Result<Self, String> {
+        let mut textures = Self::new();
+        let load = |id: u8, name: &str, path: &str| {
+            Image::load(Path::new(path))
+                .map_err(|e| format!("texture of tile {} ({}): {}", id, name, e))
+        };
+
+        for tile in map.palette().iter() {
+            if let WallAppearance::Texture(path) = &tile.wall {
+                textures.insert_wall(tile.id, load(tile.id, &tile.name, path)?);
+            }
+            if let Some(WallAppearance::Texture(path)) = &tile.floor {
+                textures.insert_floor(tile.id, load(tile.id, &tile.name, path)?);
+            }
+        }
+
+        Ok(textures)
+    }
+
+    /// Sets the wall texture of a tile, replacing the previous one.
+    pub fn insert_wall(&mut self, tile: u8, image: Image) {
+        self.walls.insert(tile, image);
+        self.touch();
+    }
+
+    /// Sets the floor texture of a tile, replacing the previous one.
+    pub fn insert_floor(&mut self, tile: u8, image: Image) {
+        self.floors.insert(tile, image);
+        self.touch();
+    }
+
+    /// Sets the sprite sheet billboard sprites are looked up in, replacing the previous one.
+    pub fn set_sprite_sheet(&mut self, sprite_sheet: SpriteSheet) {
+        self.sprite_sheet = Some(sprite_sheet);
+        self.touch();
+    }
+
+    /// Returns the wall texture of a tile.
+    pub fn wall(&self, tile: u8) -> Option<&Image> {
+        self.walls.get(&tile)
+    }
+
+    /// Returns the floor texture of a tile.
+    pub fn floor(&self, tile: u8) -> Option<&Image> {
+        self.floors.get(&tile)
+    }
+
+    /// Returns the sprite sheet billboard sprites are looked up in.
+    pub fn sprite_sheet(&self) -> Option<&SpriteSheet> {
+        self.sprite_sheet.as_ref()
+    }
+
+    /// Returns true if no tile has a wall or floor texture.
+    pub fn is_empty(&self) -> bool {
+        self.walls.is_empty() && self.floors.is_empty()
+    }
+
+    /// Returns the revision of the textures, which changes every time a texture is set.
+    ///
+    /// Clones share the revision of their original until either of them changes, so two sets
+    /// of textures with the same revision hold the same images.
+    #[cfg_attr(feature = "fixed-function", allow(dead_code))]
+    pub(crate) fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Gives the textures a new revision after one of them changed.
+    fn touch(&mut self) {
+        self.revision = LAST_REVISION.fetch_add(1, Ordering::Relaxed) + 1;
+    }
+}
@@ -0,0 +1,308 @@
+//! # RRM Error Module
+//!
+//! This module provides the [`RrmError`] type returned when a map can't be loaded or saved.
+//! Every error that points into a map file carries the file path together with the
+//! 1-based line and column of the problem, so it can be shown to level designers as is.
+
+use std::path::PathBuf;
+use std::*;
+
+/// An error that occurred while loading or saving a .rrm map file.
+#[derive(Debug)]
+pub enum RrmError {
+    /// The map file or folder couldn't be read.
+    Io {
+        /// The path that couldn't be read.
+        path: PathBuf,
+        /// The underlying I/O error.
+        source: io::Error,
+    },
+    /// The file has no `SIZE=` header before the first row.
+    MissingHeader {
+        /// The path to the map file.
+        path: PathBuf,
+        /// The line where the header was expected.
+        line: usize,
+    },
+    /// The `SIZE=` header holds something that isn't a valid map size.
+    BadSize {
+        /// The path to the map file.
+        path: PathBuf,
+        /// The line of the header.
+        line: usize,
+        /// The column where the size value starts.
+        column: usize,
+        /// The size value as written in the file.
+        value: String,
+    },
+    /// A `TILE=` header doesn't describe a valid tile.
+    BadTile {
+        /// The path to the map file.
+        path: PathBuf,
+        /// The line of the header.
+        line: usize,
+        /// The column of the invalid field.
+        column: usize,
+        /// What is wrong with the field.
+        message: String,
+    },
+    /// A metadata header holds a value that can't be parsed.
+    BadHeader {
+        /// The path to the map file.
+        path: PathBuf,
+        /// The line of the header.
+        line: usize,
+        /// The column where the value starts.
+        column: usize,
+        /// The key of the header, e.g. `FLOOR_COLOR`.
+        key: String,
+        /// What is wrong with the value.
+        message: String,
+    },
+    /// A row holds a different number of tiles than the map width.
+    RaggedRow {
+        /// The path to the map file.
+        path: PathBuf,
+        /// The line of the row.
+        line: usize,
+        /// The number of tiles every row should have.
+        expected: usize,
+        /// The number of tiles found in the row.
+        found: usize,
+    },
+    /// The file ends before all rows of the map have been read.
+    MissingRows {
+        /// The path to the map file.
+        path: PathBuf,
+        /// The line where the next row was expected.
+        line: usize,
+        /// The number of rows the map should have.
+        expected: usize,
+        /// The number of rows found in the file.
+        found: usize,
+    },
+    /// A tile isn't one of the tile ids in the map's palette.
+    UnknownTile {
+        /// The path to the map file.
+        path: PathBuf,
+        /// The line of the tile.
+        line: usize,
+        /// The column where the tile starts.
+        column: usize,
+        /// The tile as written in the file.
+        value: String,
+    },
+    /// The map holds more than one `P` player start cell.
+    DuplicatePlayerStart {
+        /// The path to the map file.
+        path: PathBuf,
+        /// The line of the second `P` cell.
+        line: usize,
+        /// The column of the second `P` cell.
+        column: usize,
+    },
+    /// The `P` player start cell is stored as tile 0, but the palette has no passable tile 0.
+    BlockedPlayerStart {
+        /// The path to the map file.
+        path: PathBuf,
+        /// The line of the `P` cell.
+        line: usize,
+        /// The column of the `P` cell.
+        column: usize,
+    },
+    /// Unexpected text after a row or after the last row of the map.
+    TrailingGarbage {
+        /// The path to the map file.
+        path: PathBuf,
+        /// The line of the unexpected text.
+        line: usize,
+        /// The column where the unexpected text starts.
+        column: usize,
+        /// The unexpected text.
+        text: String,
+    },
+    /// The map holds something that can't be written as .rrm text and read back unchanged.
+    Unwritable {
+        /// The path to the map file, empty if the map isn't written to a file.
+        path: PathBuf,
+        /// What can't be written and why.
+        message: String,
+    },
+}
+
+impl RrmError {
+    /// Returns the path of the file or folder the error belongs to.
+    pub fn path(&self) -> &PathBuf {
+        match self {
+            RrmError::Io { path, .. }
+            | RrmError::MissingHeader { path, .. }
+            | RrmError::BadSize { path, .. }
+            | RrmError::BadTile { path, .. }
+            | RrmError::BadHeader { path, .. }
+            | RrmError::RaggedRow { path, .. }
+            | RrmError::MissingRows { path, .. }
+            | RrmError::UnknownTile { path, .. }
+            | RrmError::DuplicatePlayerStart { path, .. }
+            | RrmError::BlockedPlayerStart { path, .. }
+            | RrmError::TrailingGarbage { path, .. }
+            | RrmError::Unwritable { path, .. } => path,
+        }
+    }
+
+    /// Returns the 1-based line the error points to, if it points into a file.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            RrmError::Io { .. } | RrmError::Unwritable { .. } => None,
+            RrmError::MissingHeader { line, .. }
+            | RrmError::BadSize { line, .. }
+            | RrmError::BadTile { line, .. }
+            | RrmError::BadHeader { line, .. }
+            | RrmError::RaggedRow { line, .. }
+            | RrmError::MissingRows { line, .. }
+            | RrmError::UnknownTile { line, .. }
+            | RrmError::DuplicatePlayerStart { line, .. }
+            | RrmError::BlockedPlayerStart { line, .. }
+            | RrmError::TrailingGarbage { line, .. } => Some(*line),
+        }
+    }
+}
+
+impl fmt::Display for RrmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RrmError::Io { path, source } => {
+                write!(f, "{}: {}", path.display(), source)
+            }
+            RrmError::MissingHeader { path, line } => {
+                write!(
+                    f,
+                    "{}:{}:1: expected a `SIZE=` header",
+                    path.display(),
+                    line
+                )
+            }
+            RrmError::BadSize {
+                path,
+                line,
+                column,
+                value,
+            } => write!(
+                f,
+                "{}:{}:{}: invalid map size `{}`, expected `SIZE=8` or `SIZE=32x48`",
+                path.display(),
+                line,
+                column,
+                value
+            ),
+            RrmError::BadTile {
+                path,
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "{}:{}:{}: invalid tile definition, {}",
+                path.display(),
+                line,
+                column,
+                message
+            ),
+            RrmError::BadHeader {
+                path,
+                line,
+                column,
+                key,
+                message,
+            } => write!(
+                f,
+                "{}:{}:{}: invalid `{}` header, {}",
+                path.display(),
+                line,
+                column,
+                key,
+                message
+            ),
+            RrmError::RaggedRow {
+                path,
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{}:{}:1: row has {} tiles but the map is {} tiles wide",
+                path.display(),
+                line,
+                found,
+                expected
+            ),
+            RrmError::MissingRows {
+                path,
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{}:{}:1: expected {} rows but found only {}",
+                path.display(),
+                line,
+                expected,
+                found
+            ),
+            RrmError::UnknownTile {
+                path,
+                line,
+                column,
+                value,
+            } => write!(
+                f,
+                "{}:{}:{}: unknown tile `{}`",
+                path.display(),
+                line,
+                column,
+                value
+            ),
+            RrmError::DuplicatePlayerStart { path, line, column } => write!(
+                f,
+                "{}:{}:{}: the map already has a `P` player start",
+                path.display(),
+                line,
+                column
+            ),
+            RrmError::BlockedPlayerStart { path, line, column } => write!(
+                f,
+                "{}:{}:{}: the `P` player start needs a passable tile 0 in the palette",
+                path.display(),
+                line,
+                column
+            ),
+            RrmError::TrailingGarbage {
+                path,
+                line,
+                column,
+                text,
+            } => write!(
+                f,
+                "{}:{}:{}: unexpected `{}`",
+                path.display(),
+                line,
+                column,
+                text
+            ),
+            RrmError::Unwritable { path, message } if path.as_os_str().is_empty() => {
+                write!(f, "can't write the map, {}", message)
+            }
+            RrmError::Unwritable { path, message } => {
+                write!(f, "{}: can't write the map, {}", path.display(), message)
+            }
+        }
+    }
+}
+
+impl error::Error for RrmError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            RrmError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,249 @@
+//! # Map Data Module
+//!
+//! This module provides the [`Map`] type, an owned in-memory representation of a loaded map.
+//! Maps are plain data, so they can be loaded on any thread and several of them can be
+//! held at once (e.g. the current level and a preloaded next level).
+//!
+//! ## World coordinates
+//!
+//! Positions of the player, entities and ray hits are given in world units:
+//!
+//! - The origin is the top-left corner of tile (0, 0)
+//! - x grows to the right along the columns, y grows down along the rows
+//! - Every tile is a square of [`Map::cube_size`] world units, so tile (x, y) covers
+//!   `x * cube_size..(x + 1) * cube_size` horizontally and the same range of `y` vertically
+//! - Angles are in radians, 0 faces along the x-axis and angles grow towards the y-axis
+//!
+//! The 2D map is drawn with one world unit per pixel, and in the 3D view a wall as far away
+//! as it is wide is drawn as high as the screen. The cube size therefore only sets the scale
+//! of the world and stays the same no matter how large the map is.
+
+use super::metadata::MapMetadata;
+use super::palette::{Palette, Tile};
+use super::spawn::EntitySpawn;
+
+/// The size of each map cube in world units if the map doesn't set one.
+pub const DEFAULT_CUBE_SIZE: f32 = 64.0;
+
+/// A loaded game map.
+///
+/// The tiles are stored row by row as tile ids, the [`Palette`] of the map describes what
+/// each id stands for. Without a custom palette 0 represents an empty tile and 1 represents a wall.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Map {
+    /// The width of the map in tiles.
+    width: usize,
+    /// The height of the map in tiles.
+    height: usize,
+    /// The size of each map cube in world units.
+    cube_size: f32,
+    /// The tiles of the map, `width * height` entries stored row by row.
+    data: Vec<u8>,
+    /// The tile types used by the map.
+    palette: Palette,
+    /// The metadata headers of the map.
+    metadata: MapMetadata,
+    /// The entities placed in the map.
+    entity_spawns: Vec<EntitySpawn>,
+}
+
+impl Map {
+    /// Creates a new map from its tiles using the default palette.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The width of the map in tiles.
+    /// * `height` - The height of the map in tiles.
+    /// * `data` - The tiles of the map stored row by row.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` doesn't hold exactly `width * height` tiles.
+    pub fn new(width: usize, height: usize, data: Vec<u8>) -> Self {
+        Self::with_palette(width, height, data, Palette::default())
+    }
+
+    /// Creates a new map from its tiles and tile palette.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The width of the map in tiles.
+    /// * `height` - The height of the map in tiles.
+    /// * `data` - The tiles of the map stored row by row.
+    /// * `palette` - The tile types used by the map.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` doesn't hold exactly `width * height` tiles.
+    pub fn with_palette(width: usize, height: usize, data: Vec<u8>, palette: Palette) -> Self {
+        assert_eq!(
+            data.len(),
+            width * height,
+            "map data must hold width * height tiles"
+        );
+
+        Self {
+            width,
+            height,
+            cube_size: DEFAULT_CUBE_SIZE,
+            data,
+            palette,
+            metadata: MapMetadata::default(),
+            entity_spawns: Vec::new(),
+        }
+    }
+
+    /// Returns the width of the map in tiles.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the map in tiles.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the size of each map cube in world units.
+    pub fn cube_size(&self) -> f32 {
+        self.cube_size
+    }
+
+    /// Sets the size of each map cube in world units.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cube size isn't a positive number.
+    pub fn set_cube_size(&mut self, cube_size: f32) {
+        assert!(
+            cube_size.is_finite() && cube_size > 0.0,
+            "cube size must be a positive number"
+        );
+        self.cube_size = cube_size;
+    }
+
+    /// Returns the tiles of the map stored row by row.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the tile palette of the map.
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    /// Replaces the tile palette of the map.
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    /// Returns the metadata headers of the map.
+    pub fn metadata(&self) -> &MapMetadata {
+        &self.metadata
+    }
+
+    /// Returns the metadata headers of the map for editing.
+    pub fn metadata_mut(&mut self) -> &mut MapMetadata {
+        &mut self.metadata
+    }
+
+    /// Returns the entities placed in the map.
+    pub fn entity_spawns(&self) -> &[EntitySpawn] {
+        &self.entity_spawns
+    }
+
+    /// Returns the entities placed in the map for editing.
+    pub fn entity_spawns_mut(&mut self) -> &mut Vec<EntitySpawn> {
+        &mut self.entity_spawns
+    }
+
+    /// Returns where the player starts in world units.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<(f32, f32, f32)>` - The player's x-coordinate, y-coordinate and viewing angle in radians,
+    ///   or `None` if the map doesn't define a player start.
+    pub fn player_start(&self) -> Option<(f32, f32, f32)> {
+        self.metadata.player_start.map(|start| {
+            let (x, y) = self.tile_to_world(start.x, start.y);
+            (x, y, start.angle.to_radians())
+        })
+    }
+
+    /// Returns the tile type with the given id from the map's palette.
+    pub fn tile_type(&self, id: u8) -> Option<&Tile> {
+        self.palette.get(id)
+    }
+
+    /// Returns the tile at the given tile coordinates, or `None` if they are outside of the map.
+    pub fn tile(&self, x: usize, y: usize) -> Option<u8> {
+        if x < self.width && y < self.height {
+            Some(self.data[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    /// Sets the tile at the given tile coordinates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates are outside of the map.
+    pub fn set_tile(&mut self, x: usize, y: usize, tile: u8) {
+        assert!(x < self.width && y < self.height, "tile outside of the map");
+        self.data[y * self.width + x] = tile;
+    }
+
+    /// Returns the coordinates of the tile containing the given world position,
+    /// or `None` if it is outside of the map.
+    pub fn world_to_tile(&self, world_x: f32, world_y: f32) -> Option<(usize, usize)> {
+        if world_x < 0.0 || world_y < 0.0 {
+            return None;
+        }
+
+        let map_x = (world_x / self.cube_size) as usize;
+        let map_y = (world_y / self.cube_size) as usize;
+        if map_x < self.width && map_y < self.height {
+            Some((map_x, map_y))
+        } else {
+            None
+        }
+    }
+
+    /// Converts a position in tiles (e.g. 1.5 for the center of the second tile) to world units.
+    pub fn tile_to_world(&self, tile_x: f32, tile_y: f32) -> (f32, f32) {
+        (tile_x * self.cube_size, tile_y * self.cube_size)
+    }
+
+    /// Returns the tile at the given world position, or `None` if it is outside of the map.
+    pub fn tile_at(&self, world_x: f32, world_y: f32) -> Option<u8> {
+        self.world_to_tile(world_x, world_y)
+            .and_then(|(map_x, map_y)| self.tile(map_x, map_y))
+    }
+
+    /// Returns whether the tile at the given tile coordinates is a wall, i.e. solid in the palette.
+    ///
+    /// Everything outside of the map counts as a wall.
+    pub fn is_wall(&self, x: usize, y: usize) -> bool {
+        self.tile(x, y)
+            .is_none_or(|tile| self.palette.is_solid(tile))
+    }
+}
+
+impl Default for Map {
+    /// Returns the built-in 8x8 map that is used when no map file has been loaded.
+    fn default() -> Self {
+        #[rustfmt::skip]
+        let data = vec![
+            1, 1, 1, 1, 1, 1, 1, 1,
+            1, 0, 0, 0, 0, 0, 0, 1,
+            1, 0, 1, 0, 0, 1, 0, 1,
+            1, 0, 0, 0, 0, 0, 0, 1,
+            1, 0, 0, 1, 0, 0, 0, 1,
+            1, 0, 0, 0, 0, 1, 0, 1,
+            1, 0, 1, 0, 0, 0, 0, 1,
+            1, 1, 1, 1, 1, 1, 1, 1,
+        ];
+
+        Self::new(8, 8, data)
+    }
+}
@@ -0,0 +1,92 @@
+//! # Map Metadata Module
+//!
+//! This module provides the [`MapMetadata`] of a map, which holds the optional `KEY=VALUE`
+//! headers of a .rrm file:
+//!
+//! ```rrm
+//! NAME=The Cellar
+//! AUTHOR=Jane Doe
+//! PLAYER_START=1.5,1.5,90
+//! CEILING_COLOR=#303030
+//! FLOOR_COLOR=#606060
+//! FOG=#101010,2,8
+//! ```
+//!
+//! Headers the engine doesn't know are kept in [`MapMetadata::extra`], so maps made for
+//! newer versions of the engine can still be loaded by older ones.
+
+use sdl2::pixels::Color;
+
+/// Where the player starts on a map and which way they face.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlayerStart {
+    /// The x-coordinate in tiles, e.g. 1.5 for the center of the second column.
+    pub x: f32,
+    /// The y-coordinate in tiles, e.g. 1.5 for the center of the second row.
+    pub y: f32,
+    /// The viewing angle in degrees, 0 faces along the x-axis.
+    pub angle: f32,
+}
+
+/// Distance fog that fades walls into a colour the further away they are.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fog {
+    /// The colour walls fade into.
+    pub color: Color,
+    /// The distance in tiles where the fog starts.
+    pub start: f32,
+    /// The distance in tiles where walls are completely covered by the fog.
+    pub end: f32,
+}
+
+impl Fog {
+    /// Returns how much of the fog colour covers a wall, from 0 (none) to 1 (all).
+    ///
+    /// # Arguments
+    ///
+    /// * `distance` - The distance of the wall in tiles.
+    pub fn factor(&self, distance: f32) -> f32 {
+        if distance <= self.start {
+            0.0
+        } else if distance >= self.end {
+            1.0
+        } else {
+            (distance - self.start) / (self.end - self.start)
+        }
+    }
+
+    /// Returns whether the fog starts at a distance of at least 0 and ends at a finite
+    /// distance after it, as the `FOG` header requires.
+    pub(crate) fn is_valid(&self) -> bool {
+        self.start >= 0.0 && self.start < self.end && self.end.is_finite()
+    }
+}
+
+/// The metadata headers of a map.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MapMetadata {
+    /// The display name of the map (`NAME=`).
+    pub name: Option<String>,
+    /// The author of the map (`AUTHOR=`).
+    pub author: Option<String>,
+    /// Where the player starts (`PLAYER_START=x,y,angle`).
+    pub player_start: Option<PlayerStart>,
+    /// The colour of the ceiling (`CEILING_COLOR=#RRGGBB`).
+    pub ceiling_color: Option<Color>,
+    /// The colour of the floor (`FLOOR_COLOR=#RRGGBB`).
+    pub floor_color: Option<Color>,
+    /// The distance fog (`FOG=#RRGGBB,start,end`), `None` for no fog.
+    pub fog: Option<Fog>,
+    /// Headers the engine doesn't know, in the order they appear in the file.
+    pub extra: Vec<(String, String)>,
+}
+
+impl MapMetadata {
+    /// Returns the value of a header the engine doesn't know.
+    pub fn get_extra(&self, key: &str) -> Option<&str> {
+        self.extra
+            .iter()
+            .find(|(extra_key, _)| extra_key == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
@@ -2,18 +2,35 @@
 //!
 //! The engine uses a custom map format (.rrm) for defining game maps. Map files follow this structure:
 //! ```rrm
+//! # The first level
+//! NAME=Cellar
 //! SIZE=8
+//! TILE=2,brick,solid,#8B4513,#A0522D
+//!
 //! [1, 1, 1, 1, 1, 1, 1, 1,],
 //! [1, 0, 0, 0, 0, 0, 0, 1,],
-//! [1, 0, 0, 0, 0, 1, 0, 1,],
-//! [1, 1, 1, 1, 0, 1, 1, 1,],
+//! [1, 0, 0, 0, 0, 2, 0, 1,],
+//! [1, 1, 1, 1, 0, 2, 1, 1,],
 //! [1, 0, 0, 1, 0, 0, 0, 1,],
 //! [1, 0, 0, 0, 0, 0, 0, 1,],
 //! [1, 0, 0, 1, 0, 0, 0, 1,],
 //! [1, 1, 1, 1, 1, 1, 1, 1,],
 //! ```
 
+pub mod error;
+pub mod map;
+pub mod metadata;
+pub mod palette;
 pub mod rrm_support;
+pub mod rrm_writer;
+pub mod spawn;
 
-pub use rrm_support::map_initialize;
-pub use rrm_support::{MAP_CUBE_SIZE, MAP_DATA, MAP_HEIGHT, MAP_WIDTH};
+pub use error::RrmError;
+pub use map::{Map, DEFAULT_CUBE_SIZE};
+pub use metadata::{Fog, MapMetadata, PlayerStart};
+pub use palette::{Palette, Tile, WallAppearance};
+pub use rrm_support::{
+    list_maps, load_map, load_map_from, map_initialize, parse_map_data, read_map_data, FileInfo,
+};
+pub use rrm_writer::{save_map, write_map_data};
+pub use spawn::{EntityKind, EntitySpawn};
@@ -0,0 +1,138 @@
+//! # Tile Palette Module
+//!
+//! This module provides the tile palette of a map. The palette maps every tile id used
+//! in the map grid to a [`Tile`] that describes whether the tile blocks rays and movement
+//! and how it looks in the 3D view and on the 2D map.
+//!
+//! Maps declare their tiles with `TILE=` headers:
+//!
+//! ```rrm
+//! TILE=2,brick,solid,#8B4513,#A0522D
+//! TILE=3,stone,solid,textures/stone.bmp,#808080
+//! TILE=4,carpet,passable,#000000,#800000,textures/carpet.bmp
+//! ```
+//!
+//! The fields are the tile id, the name, `solid` or `passable`, the wall colour (`#RRGGBB`)
+//! or the path to a wall texture, and the colour of the tile on the 2D map (`#RRGGBB` or `#RRGGBBAA`).
+//! Passable tiles can have an optional sixth field with the floor colour or the path to a
+//! floor texture.
+
+use sdl2::pixels::Color;
+use std::collections::BTreeMap;
+
+/// How the walls or the floor of a tile look in the 3D view.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WallAppearance {
+    /// The surface is drawn in a flat colour.
+    Color(Color),
+    /// The surface is drawn with the texture stored at this path.
+    Texture(String),
+}
+
+/// A single tile type of a map.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tile {
+    /// The id of the tile as used in the map grid.
+    pub id: u8,
+    /// The name of the tile, e.g. `brick`.
+    pub name: String,
+    /// Whether the tile blocks rays and movement.
+    pub solid: bool,
+    /// How the walls of the tile look in the 3D view.
+    pub wall: WallAppearance,
+    /// The colour of the tile on the 2D map.
+    pub map_color: Color,
+    /// How the floor of the tile looks in the 3D view, `None` for the floor colour of the map.
+    pub floor: Option<WallAppearance>,
+}
+
+impl Tile {
+    /// Returns the flat colour of the tile's walls.
+    ///
+    /// Textured walls fall back to the map colour.
+    pub fn wall_color(&self) -> Color {
+        match &self.wall {
+            WallAppearance::Color(color) => *color,
+            WallAppearance::Texture(_) => self.map_color,
+        }
+    }
+}
+
+/// The tile palette of a map, ordered by tile id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    tiles: BTreeMap<u8, Tile>,
+}
+
+impl Palette {
+    /// Creates an empty palette.
+    pub fn new() -> Self {
+        Self {
+            tiles: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a tile to the palette, replacing any tile with the same id.
+    pub fn insert(&mut self, tile: Tile) {
+        self.tiles.insert(tile.id, tile);
+    }
+
+    /// Returns the tile with the given id.
+    pub fn get(&self, id: u8) -> Option<&Tile> {
+        self.tiles.get(&id)
+    }
+
+    /// Returns whether the palette contains a tile with the given id.
+    pub fn contains(&self, id: u8) -> bool {
+        self.tiles.contains_key(&id)
+    }
+
+    /// Returns whether the tile with the given id blocks rays and movement.
+    ///
+    /// Tiles that aren't in the palette count as solid.
+    pub fn is_solid(&self, id: u8) -> bool {
+        self.get(id).is_none_or(|tile| tile.solid)
+    }
+
+    /// Returns an iterator over all tiles ordered by id.
+    pub fn iter(&self) -> impl Iterator<Item = &Tile> {
+        self.tiles.values()
+    }
+}
+
+impl Default for Palette {
+    /// Returns the palette used by maps that don't declare their own tiles,
+    /// where 0 is an empty tile and 1 is a wall.
+    fn default() -> Self {
+        let mut palette = Self::new();
+        palette.insert(Tile {
+            id: 0,
+            name: String::from("empty"),
+            solid: false,
+            wall: WallAppearance::Color(Color::RGB(0, 0, 0)),
+            map_color: Color::RGBA(0, 0, 0, 0),
+            floor: None,
+        });
+        palette.insert(Tile {
+            id: 1,
+            name: String::from("wall"),
+            solid: true,
+            wall: WallAppearance::Color(Color::RGB(0, 255, 0)),
+            map_color: Color::RGB(255, 255, 255),
+            floor: None,
+        });
+        palette
+    }
+}
+
+/// Parses a colour written as `#RRGGBB` or `#RRGGBBAA`.
+pub fn parse_color(value: &str) -> Option<Color> {
+    let hex = value.strip_prefix('#')?;
+    if !hex.is_ascii() || (hex.len() != 6 && hex.len() != 8) {
+        return None;
+    }
+
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+    Some(Color::RGBA(channel(0)?, channel(2)?, channel(4)?, alpha))
+}
@@ -1,80 +1,174 @@
 //! # Game Engine Map Module
 //!
 //! This module provides functionality for initializing and managing game maps.
-//! It includes functions for reading map data from files and allocating map variables.
+//! It includes functions for reading map data from files into a [`Map`].
 
 // --- Imports ---
+use super::error::RrmError;
+use super::map::Map;
+use super::metadata::{Fog, MapMetadata, PlayerStart};
+use super::palette::{parse_color, Palette, Tile, WallAppearance};
+use super::spawn::{EntityKind, EntitySpawn};
 use std::path::Path;
+use std::time::SystemTime;
 use std::*;
 
-// --- Variables ---
-// Defaults
+// --- Constants ---
+/// The folder [`load_map`] looks in when it is given a map name instead of a path.
+pub const MAP_FOLDER: &str = "./assets/maps";
 
-/// The size of the map (width and height).
-static mut SIZE: i32 = 8;
+/// The cell that marks the player start in a map row.
+const PLAYER_START_CELL: &str = "P";
 
-/// The width of the map in tiles.
-pub static mut MAP_WIDTH: usize = 8;
+/// The tile stored in the map grid where the player start cell was.
+const PLAYER_START_TILE: u8 = 0;
 
-/// The height of the map in tiles.
-pub static mut MAP_HEIGHT: usize = 8;
-
-/// The size of each map cube in pixels or units.
-pub static mut MAP_CUBE_SIZE: f32 = 64.0;
-
-/// The actual map data, where 0 represents an empty tile and 1 represents a wall.
-pub static mut MAP_DATA: [[u8; 8]; 8] = [
-    [1, 1, 1, 1, 1, 1, 1, 1],
-    [1, 0, 0, 0, 0, 0, 0, 1],
-    [1, 0, 1, 0, 0, 1, 0, 1],
-    [1, 0, 0, 0, 0, 0, 0, 1],
-    [1, 0, 0, 1, 0, 0, 0, 1],
-    [1, 0, 0, 0, 0, 1, 0, 1],
-    [1, 0, 1, 0, 0, 0, 0, 1],
-    [1, 1, 1, 1, 1, 1, 1, 1],
+/// The headers the loader understands, all other headers are kept in [`MapMetadata::extra`].
+pub(crate) const HEADER_KEYS: [&str; 11] = [
+    "SIZE",
+    "PALETTE",
+    "TILE",
+    "ENTITY",
+    "CUBE_SIZE",
+    "NAME",
+    "AUTHOR",
+    "PLAYER_START",
+    "CEILING_COLOR",
+    "FLOOR_COLOR",
+    "FOG",
 ];
 
 // --- Logic ---
 /// Represents information about a file in the map directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FileInfo {
     /// The name of the file.
     pub name: String,
     /// The full path to the file.
     pub path: String,
+    /// The size of the file in bytes.
+    pub size: u64,
+    /// The time the file was last modified, if the platform reports it.
+    pub modified: Option<SystemTime>,
+    /// Whether the entry is a file (or a link to one) rather than a directory.
+    pub is_file: bool,
+}
+
+impl FileInfo {
+    /// Returns the name of the map stored in the file, which is the file name without the extension.
+    ///
+    /// This is the name shown in map selection menus and accepted by [`load_map`].
+    pub fn map_name(&self) -> &str {
+        Path::new(&self.name)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or(&self.name)
+    }
+
+    /// Returns whether the entry is a .rrm map file, the extension may be in any case.
+    pub fn is_map(&self) -> bool {
+        self.is_file
+            && Path::new(&self.name)
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("rrm"))
+    }
 }
 
 /// Initializes the map by reading data from files in the specified folder.
 ///
+/// The first map in the order returned by [`list_maps`] is loaded, so the same map is
+/// picked on every machine. Use [`load_map`] to load a specific map.
+///
+/// # Arguments
+///
+/// * `folder_location` - A string slice that holds the path to the folder containing map files.
+///
+/// # Returns
+///
+/// * `Result<Map, RrmError>` - The loaded map if successful, or an error if the folder doesn't exist, contains no map files or a map file is invalid.
+pub fn map_initialize(folder_location: &str) -> Result<Map, RrmError> {
+    match list_maps(folder_location)?.first() {
+        Some(file_info) => read_map_data(Path::new(&file_info.path)),
+        None => Err(RrmError::Io {
+            path: Path::new(folder_location).to_path_buf(),
+            source: io::Error::new(io::ErrorKind::NotFound, "No .rrm map file found"),
+        }),
+    }
+}
+
+/// Lists all map files in the specified folder.
+///
 /// # Arguments
 ///
 /// * `folder_location` - A string slice that holds the path to the folder containing map files.
 ///
 /// # Returns
 ///
-/// * `io::Result<()>` - Ok(()) if successful, or an error if the folder doesn't exist or there's an issue reading files.
-pub fn map_initialize(folder_location: &str) -> io::Result<()> {
+/// * `Result<Vec<FileInfo>, RrmError>` - The .rrm files in the folder sorted by name, or an error if the folder can't be read.
+pub fn list_maps(folder_location: &str) -> Result<Vec<FileInfo>, RrmError> {
     let path = Path::new(&folder_location);
+    let io_error = |source| RrmError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
 
-    if path.exists() {
-        match read_dir_to_string(String::from(folder_location)) {
-            Ok(files) => {
-                for file_info in files {
-                    let path_to_file = Path::new(&file_info.path);
-                    if path_to_file.extension().and_then(|s| s.to_str()) == Some("rrm") {
-                        read_map_data(path_to_file);
-                        // We only need to process one file, so we can break here
-                        break;
-                    }
-                }
-                Ok(())
-            }
-            Err(e) => Err(e),
-        }
-    } else {
-        Err(io::Error::new(
+    if !path.exists() {
+        return Err(io_error(io::Error::new(
             io::ErrorKind::NotFound,
             "Path does not exist",
-        ))
+        )));
+    }
+
+    let files = read_dir_to_string(String::from(folder_location)).map_err(io_error)?;
+    Ok(files.into_iter().filter(FileInfo::is_map).collect())
+}
+
+/// Loads a specific map by name or path.
+///
+/// If `name_or_path` points to an existing file, that file is loaded. Otherwise it is
+/// treated as a map name and looked up in [`MAP_FOLDER`], see [`load_map_from`].
+///
+/// # Arguments
+///
+/// * `name_or_path` - The name of the map (e.g. `level_1`) or the path to a .rrm file.
+///
+/// # Returns
+///
+/// * `Result<Map, RrmError>` - The loaded map, or an error if it can't be found or is invalid.
+pub fn load_map(name_or_path: &str) -> Result<Map, RrmError> {
+    let path = Path::new(name_or_path);
+
+    if path.is_file() {
+        read_map_data(path)
+    } else {
+        load_map_from(MAP_FOLDER, name_or_path)
+    }
+}
+
+/// Loads a map by name from the specified folder.
+///
+/// The name may be given with or without the `.rrm` extension.
+///
+/// # Arguments
+///
+/// * `folder_location` - A string slice that holds the path to the folder containing map files.
+/// * `name` - The name of the map (e.g. `level_1` or `level_1.rrm`).
+///
+/// # Returns
+///
+/// * `Result<Map, RrmError>` - The loaded map, or an error if it can't be found or is invalid.
+pub fn load_map_from(folder_location: &str, name: &str) -> Result<Map, RrmError> {
+    let maps = list_maps(folder_location)?;
+
+    match maps
+        .iter()
+        .find(|file_info| file_info.name == name || file_info.map_name() == name)
+    {
+        Some(file_info) => read_map_data(Path::new(&file_info.path)),
+        None => Err(RrmError::Io {
+            path: Path::new(folder_location).join(name),
+            source: io::Error::new(io::ErrorKind::NotFound, "No map with this name found"),
+        }),
     }
 }
 
@@ -86,79 +180,528 @@ pub fn map_initialize(folder_location: &str) -> io::Result<()> {
 ///
 /// # Returns
 ///
-/// * `io::Result<Vec<FileInfo>>` - A vector of FileInfo structs if successful, or an error if there's an issue reading the directory.
+/// * `io::Result<Vec<FileInfo>>` - A vector of FileInfo structs sorted by name if successful, or an error if there's an issue reading the directory.
 pub fn read_dir_to_string(folder_location: String) -> io::Result<Vec<FileInfo>> {
     let path = Path::new(&folder_location);
     let mut file_names = Vec::new();
 
     for entry in fs::read_dir(path)? {
         let entry = entry?;
+        let metadata = entry.metadata()?;
         file_names.push(FileInfo {
             name: entry.file_name().to_string_lossy().into_owned(),
             path: entry.path().to_string_lossy().into_owned(),
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+            is_file: entry.path().is_file(),
         });
     }
 
+    // The order of read_dir is platform specific, sort it so every machine sees the same list
+    file_names.sort_by(|a, b| a.name.cmp(&b.name));
+
     Ok(file_names)
 }
 
-/// Reads map data from a file and updates the global map variables.
+/// Reads map data from a file.
 ///
 /// # Arguments
 ///
 /// * `path_to_file` - A reference to a Path that points to the map file to read.
 ///
-/// # Safety
+/// # Returns
 ///
-/// This function uses unsafe code to modify static mutable variables. Ensure that it's called in a single-threaded context or with proper synchronization.
-pub fn read_map_data(path_to_file: &Path) {
-    let file_data = fs::read_to_string(path_to_file).unwrap();
-    let data = file_data.lines().collect::<Vec<&str>>();
+/// * `Result<Map, RrmError>` - The map stored in the file, or an error describing where the file is invalid.
+pub fn read_map_data(path_to_file: &Path) -> Result<Map, RrmError> {
+    let file_data = fs::read_to_string(path_to_file).map_err(|source| RrmError::Io {
+        path: path_to_file.to_path_buf(),
+        source,
+    })?;
 
-    unsafe {
-        // Parse SIZE from first line
-        if let Some(size_str) = data[0].split('=').last() {
-            SIZE = size_str.trim().parse().unwrap_or(8);
+    parse_map_data(&file_data, path_to_file)
+}
+
+/// Parses map data from the contents of a .rrm file.
+///
+/// A map file starts with `KEY=VALUE` headers in any order, followed by exactly one line
+/// per row of the map, each holding one tile id per column. Lines starting with `#` are
+/// comments, a `#` after a row starts a comment as well. Blank lines are ignored.
+///
+/// The `SIZE` header is required and holds the map size, either as `SIZE=8` for a square
+/// map or as `SIZE=32x48` for a map that is 32 tiles wide and 48 tiles high. `TILE=` headers
+/// add tiles to the default palette or replace its tiles, see [`palette`](super::palette).
+/// `CUBE_SIZE=` sets the size of each tile in world units, see [`Map::cube_size`].
+/// `ENTITY=` headers place entities, see [`spawn`](super::spawn). All other headers end up
+/// in the map's [`MapMetadata`], see [`metadata`](super::metadata).
+///
+/// A single `P` cell may mark where the player starts, facing along the x-axis. It is
+/// stored as an empty tile. A `PLAYER_START` header takes precedence over the `P` cell.
+///
+/// # Arguments
+///
+/// * `file_data` - The contents of the map file.
+/// * `path_to_file` - The path the contents were read from, used in error messages.
+///
+/// # Returns
+///
+/// * `Result<Map, RrmError>` - The parsed map, or an error describing where the contents are invalid.
+pub fn parse_map_data(file_data: &str, path_to_file: &Path) -> Result<Map, RrmError> {
+    let mut headers = Headers::default();
+    let mut new_map = Vec::new();
+    let mut rows = 0;
+    let mut last_line = 0;
+    let mut player_cell = None;
+
+    for (line_number, line) in file_data.lines().enumerate().map(|(i, line)| (i + 1, line)) {
+        last_line = line_number;
+        let trimmed = line.trim();
+
+        // Skip comments and blank lines
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
         }
 
-        // Skip first line (SIZE) and parse array lines
-        let array_lines = &data[1..];
+        // Headers may only appear before the first row
+        if rows == 0 && trimmed.contains('=') {
+            parse_header(path_to_file, line_number, line, &mut headers)?;
+            continue;
+        }
+
+        let Some((width, height)) = headers.size else {
+            return Err(RrmError::MissingHeader {
+                path: path_to_file.to_path_buf(),
+                line: line_number,
+            });
+        };
 
-        // Create a new map array and fill it with the data
-        let mut new_map = [[0u8; 8]; 8];
-        for (i, line) in array_lines.iter().enumerate() {
-            if i >= 8 {
-                break;
+        if rows < height {
+            let player_column = parse_row(
+                path_to_file,
+                line_number,
+                line,
+                width,
+                &headers.palette,
+                &mut new_map,
+            )?;
+
+            if let Some((x, column)) = player_column {
+                if player_cell.is_some() {
+                    return Err(RrmError::DuplicatePlayerStart {
+                        path: path_to_file.to_path_buf(),
+                        line: line_number,
+                        column,
+                    });
+                }
+                player_cell = Some((x, rows));
             }
+            rows += 1;
+        } else {
+            let start = line.len() - line.trim_start().len();
+            return Err(RrmError::TrailingGarbage {
+                path: path_to_file.to_path_buf(),
+                line: line_number,
+                column: column(line, start),
+                text: trimmed.to_string(),
+            });
+        }
+    }
 
-            let trimmed = line.trim().trim_matches(|c| c == '[' || c == ']');
-            let nums: Vec<u8> = trimmed
-                .split(',')
-                .map(|n| n.trim().parse().unwrap_or(0))
-                .collect();
+    let Some((width, height)) = headers.size else {
+        return Err(RrmError::MissingHeader {
+            path: path_to_file.to_path_buf(),
+            line: last_line + 1,
+        });
+    };
 
-            for (j, &num) in nums.iter().enumerate().take(8) {
-                new_map[i][j] = num;
+    if rows < height {
+        return Err(RrmError::MissingRows {
+            path: path_to_file.to_path_buf(),
+            line: last_line + 1,
+            expected: height,
+            found: rows,
+        });
+    }
+
+    // Start in the center of the P cell unless there is a PLAYER_START header
+    if let (None, Some((x, y))) = (&headers.metadata.player_start, player_cell) {
+        headers.metadata.player_start = Some(PlayerStart {
+            x: x as f32 + 0.5,
+            y: y as f32 + 0.5,
+            angle: 0.0,
+        });
+    }
+
+    let mut map = Map::with_palette(width, height, new_map, headers.palette);
+    *map.metadata_mut() = headers.metadata;
+    *map.entity_spawns_mut() = headers.entity_spawns;
+    if let Some(cube_size) = headers.cube_size {
+        map.set_cube_size(cube_size);
+    }
+    Ok(map)
+}
+
+/// The headers of a map file collected while parsing.
+#[derive(Default)]
+struct Headers {
+    /// The width and height from the `SIZE` header.
+    size: Option<(usize, usize)>,
+    /// The default palette extended by the `TILE` headers.
+    palette: Palette,
+    /// Whether a `TILE` header has been read.
+    has_tiles: bool,
+    /// The cube size from the `CUBE_SIZE` header.
+    cube_size: Option<f32>,
+    /// The entities from the `ENTITY` headers.
+    entity_spawns: Vec<EntitySpawn>,
+    /// All other headers.
+    metadata: MapMetadata,
+}
+
+/// Parses a single `KEY=VALUE` header into `headers`.
+fn parse_header(
+    path_to_file: &Path,
+    line_number: usize,
+    line: &str,
+    headers: &mut Headers,
+) -> Result<(), RrmError> {
+    let (key, value) = line.split_once('=').unwrap_or((line, ""));
+    let value_start = key.len() + 1 + (value.len() - value.trim_start().len());
+    let key = key.trim();
+    let value = value.trim();
+    let bad_header = |message: &str| RrmError::BadHeader {
+        path: path_to_file.to_path_buf(),
+        line: line_number,
+        column: column(line, value_start),
+        key: key.to_string(),
+        message: message.to_string(),
+    };
+    let metadata = &mut headers.metadata;
+
+    match key {
+        "SIZE" => headers.size = Some(parse_size_header(path_to_file, line_number, line)?),
+        "PALETTE" => match value {
+            "empty" if headers.has_tiles => {
+                return Err(bad_header("must come before the `TILE` headers"))
+            }
+            "empty" => headers.palette = Palette::new(),
+            _ => return Err(bad_header("expected `empty`")),
+        },
+        "TILE" => {
+            headers
+                .palette
+                .insert(parse_tile_header(path_to_file, line_number, line)?);
+            headers.has_tiles = true;
+        }
+        "ENTITY" => {
+            let spawn = parse_entity_header(value).ok_or_else(|| {
+                bad_header("expected `name,hostile|neutral,x,y,angle,speed,health,sprite_id`")
+            })?;
+            if headers
+                .entity_spawns
+                .iter()
+                .any(|other| other.name == spawn.name)
+            {
+                return Err(bad_header("an entity with this name already exists"));
             }
+            headers.entity_spawns.push(spawn);
         }
-        allocate_variables(new_map);
+        "CUBE_SIZE" => match value.parse::<f32>() {
+            Ok(cube_size) if cube_size.is_finite() && cube_size > 0.0 => {
+                headers.cube_size = Some(cube_size)
+            }
+            _ => return Err(bad_header("expected a positive number")),
+        },
+        "NAME" => metadata.name = Some(value.to_string()),
+        "AUTHOR" => metadata.author = Some(value.to_string()),
+        "PLAYER_START" => {
+            let numbers = value
+                .split(',')
+                .map(|number| number.trim().parse::<f32>().ok())
+                .collect::<Option<Vec<_>>>();
+            match numbers.as_deref() {
+                Some(&[x, y, angle]) if [x, y, angle].iter().all(|n| n.is_finite()) => {
+                    metadata.player_start = Some(PlayerStart { x, y, angle })
+                }
+                _ => return Err(bad_header("expected `x,y,angle` as finite numbers")),
+            }
+        }
+        "CEILING_COLOR" => {
+            metadata.ceiling_color =
+                Some(parse_color(value).ok_or_else(|| bad_header("expected a `#RRGGBB` colour"))?)
+        }
+        "FLOOR_COLOR" => {
+            metadata.floor_color =
+                Some(parse_color(value).ok_or_else(|| bad_header("expected a `#RRGGBB` colour"))?)
+        }
+        "FOG" => {
+            metadata.fog = Some(
+                parse_fog_header(value)
+                    .ok_or_else(|| bad_header("expected `#RRGGBB,start,end` with start < end"))?,
+            )
+        }
+        // Keep unknown headers so maps made for newer engines still load
+        _ => metadata.extra.push((key.to_string(), value.to_string())),
     }
+
+    Ok(())
 }
 
-/// Allocates and updates the global map variables with new map data.
-///
-/// # Arguments
+/// Parses the value of a `FOG=color,start,end` header.
+fn parse_fog_header(value: &str) -> Option<Fog> {
+    let fields = value.split(',').map(str::trim).collect::<Vec<_>>();
+    let [color, start, end] = fields[..] else {
+        return None;
+    };
+
+    let fog = Fog {
+        color: parse_color(color)?,
+        start: start.parse().ok()?,
+        end: end.parse().ok()?,
+    };
+    fog.is_valid().then_some(fog)
+}
+
+/// Parses the value of an `ENTITY=name,kind,x,y,angle,speed,health,sprite_id` header.
+fn parse_entity_header(value: &str) -> Option<EntitySpawn> {
+    let fields = value.split(',').map(str::trim).collect::<Vec<_>>();
+    let [name, kind, x, y, angle, speed, health, sprite_id] = fields[..] else {
+        return None;
+    };
+
+    let kind = match kind {
+        "hostile" => EntityKind::Hostile,
+        "neutral" => EntityKind::Neutral,
+        _ => return None,
+    };
+    if name.is_empty() {
+        return None;
+    }
+
+    Some(EntitySpawn {
+        name: name.to_string(),
+        kind,
+        x: x.parse().ok()?,
+        y: y.parse().ok()?,
+        angle: angle.parse().ok()?,
+        speed: speed.parse().ok()?,
+        health: health.parse().ok()?,
+        sprite_id: sprite_id.parse().ok()?,
+    })
+}
+
+/// Parses the `SIZE` header.
 ///
-/// * `new_map` - A 2D array representing the new map data to be allocated.
+/// Accepts either a single number for square maps or `WIDTHxHEIGHT`.
+fn parse_size_header(
+    path_to_file: &Path,
+    line_number: usize,
+    line: &str,
+) -> Result<(usize, usize), RrmError> {
+    let (key, value) = line.split_once('=').unwrap_or((line, ""));
+    let value_start = key.len() + 1 + (value.len() - value.trim_start().len());
+    let bad_size = || RrmError::BadSize {
+        path: path_to_file.to_path_buf(),
+        line: line_number,
+        column: column(line, value_start),
+        value: value.trim().to_string(),
+    };
+
+    let parse_dimension = |dimension: &str| match dimension.trim().parse::<usize>() {
+        Ok(dimension) if dimension > 0 => Ok(dimension),
+        _ => Err(bad_size()),
+    };
+
+    match value.split_once(['x', 'X']) {
+        Some((width, height)) => Ok((parse_dimension(width)?, parse_dimension(height)?)),
+        None => {
+            let size = parse_dimension(value)?;
+            Ok((size, size))
+        }
+    }
+}
+
+/// Parses a `TILE=id,name,solid|passable,wall,map_color` header.
+fn parse_tile_header(
+    path_to_file: &Path,
+    line_number: usize,
+    line: &str,
+) -> Result<Tile, RrmError> {
+    let value_start = line.find('=').map_or(0, |i| i + 1);
+    let bad_tile = |offset: usize, message: &str| RrmError::BadTile {
+        path: path_to_file.to_path_buf(),
+        line: line_number,
+        column: column(line, offset),
+        message: message.to_string(),
+    };
+
+    // Split the fields and remember where each of them starts
+    let mut fields = Vec::new();
+    let mut offset = value_start;
+    for field in line[value_start..].split(',') {
+        let field_start = offset + (field.len() - field.trim_start().len());
+        fields.push((field_start, field.trim()));
+        offset += field.len() + 1;
+    }
+
+    // The floor is optional, so split it off before matching the required fields
+    let (fields, floor) = match fields.len() {
+        6 => (&fields[..5], fields.get(5)),
+        _ => (&fields[..], None),
+    };
+    let [(id_start, id), (_, name), (solid_start, solid), (wall_start, wall), (map_start, map_color)] =
+        fields[..]
+    else {
+        return Err(bad_tile(
+            value_start,
+            "expected `TILE=id,name,solid|passable,wall,map_color[,floor]`",
+        ));
+    };
+
+    let id = id
+        .parse::<u8>()
+        .map_err(|_| bad_tile(id_start, "the id must be a number from 0 to 255"))?;
+    let solid = match solid {
+        "solid" => true,
+        "passable" => false,
+        _ => return Err(bad_tile(solid_start, "expected `solid` or `passable`")),
+    };
+    let wall = parse_appearance(wall)
+        .map_err(|message| bad_tile(wall_start, &format!("expected a wall {}", message)))?;
+    let map_color = parse_color(map_color)
+        .ok_or_else(|| bad_tile(map_start, "expected a `#RRGGBB` or `#RRGGBBAA` colour"))?;
+    let floor = floor
+        .map(|&(floor_start, floor)| {
+            parse_appearance(floor)
+                .map_err(|message| bad_tile(floor_start, &format!("expected a floor {}", message)))
+        })
+        .transpose()?;
+
+    Ok(Tile {
+        id,
+        name: name.to_string(),
+        solid,
+        wall,
+        map_color,
+        floor,
+    })
+}
+
+/// Parses the wall or floor field of a `TILE` header, either a colour or a texture path.
 ///
-/// # Safety
+/// Returns what was expected instead if the field is invalid.
+fn parse_appearance(field: &str) -> Result<WallAppearance, &'static str> {
+    if field.starts_with('#') {
+        parse_color(field)
+            .map(WallAppearance::Color)
+            .ok_or("`#RRGGBB` colour")
+    } else if field.is_empty() {
+        Err("colour or texture path")
+    } else {
+        Ok(WallAppearance::Texture(field.to_string()))
+    }
+}
+
+/// Parses a single map row such as `[1, 0, 0, 1],` and appends its tiles to `new_map`.
 ///
-/// This function uses unsafe code to modify static mutable variables. Ensure that it's called in a single-threaded context or with proper synchronization.
-pub fn allocate_variables(new_map: [[u8; 8]; 8]) {
-    unsafe {
-        MAP_WIDTH = SIZE as usize;
-        MAP_HEIGHT = SIZE as usize;
-        MAP_CUBE_SIZE = (SIZE * SIZE) as f32;
-        MAP_DATA = new_map; // This line is important
+/// The brackets and the trailing commas are optional, a `#` starts a comment.
+/// Returns the tile index and the column of the `P` cell if the row holds one.
+fn parse_row(
+    path_to_file: &Path,
+    line_number: usize,
+    line: &str,
+    width: usize,
+    palette: &Palette,
+    new_map: &mut Vec<u8>,
+) -> Result<Option<(usize, usize)>, RrmError> {
+    let content = line.split('#').next().unwrap_or(line).trim_end();
+    let mut start = line.len() - line.trim_start().len();
+    if content[start..].starts_with('[') {
+        start += 1;
     }
+
+    // Everything after the closing bracket may only be a single comma
+    let end = match content[start..].find(']') {
+        Some(offset) => {
+            let end = start + offset;
+            let rest = &content[end + 1..];
+            let rest = rest.strip_prefix(',').unwrap_or(rest);
+            if !rest.trim().is_empty() {
+                let garbage_start = content.len() - rest.trim_start().len();
+                return Err(RrmError::TrailingGarbage {
+                    path: path_to_file.to_path_buf(),
+                    line: line_number,
+                    column: column(line, garbage_start),
+                    text: rest.trim().to_string(),
+                });
+            }
+            end
+        }
+        None => content.len(),
+    };
+
+    let cells = &content[start..end];
+    let cell_count = cells.split(',').count();
+    let mut found = 0;
+    let mut offset = start;
+    let mut player_column = None;
+    for (i, cell) in cells.split(',').enumerate() {
+        let cell_start = offset + (cell.len() - cell.trim_start().len());
+        offset += cell.len() + 1;
+
+        let cell = cell.trim();
+        // Allow a trailing comma after the last tile
+        if cell.is_empty() && i + 1 == cell_count && i > 0 {
+            continue;
+        }
+
+        if cell == PLAYER_START_CELL && player_column.is_none() {
+            if palette.is_solid(PLAYER_START_TILE) {
+                return Err(RrmError::BlockedPlayerStart {
+                    path: path_to_file.to_path_buf(),
+                    line: line_number,
+                    column: column(line, cell_start),
+                });
+            }
+            new_map.push(PLAYER_START_TILE);
+            player_column = Some((found, column(line, cell_start)));
+            found += 1;
+            continue;
+        }
+
+        match cell.parse::<u8>() {
+            Ok(tile) if palette.contains(tile) => new_map.push(tile),
+            _ if cell == PLAYER_START_CELL => {
+                return Err(RrmError::DuplicatePlayerStart {
+                    path: path_to_file.to_path_buf(),
+                    line: line_number,
+                    column: column(line, cell_start),
+                })
+            }
+            _ => {
+                return Err(RrmError::UnknownTile {
+                    path: path_to_file.to_path_buf(),
+                    line: line_number,
+                    column: column(line, cell_start),
+                    value: cell.to_string(),
+                })
+            }
+        }
+        found += 1;
+    }
+
+    if found != width {
+        return Err(RrmError::RaggedRow {
+            path: path_to_file.to_path_buf(),
+            line: line_number,
+            expected: width,
+            found,
+        });
+    }
+
+    Ok(player_column)
+}
+
+/// Returns the 1-based column of the given byte offset in a line.
+fn column(line: &str, byte_offset: usize) -> usize {
+    line[..byte_offset].chars().count() + 1
 }
@@ -0,0 +1,285 @@
+//! # Map Writer Module
+//!
+//! This module provides functionality for writing a [`Map`] back out as .rrm text,
+//! e.g. from level editors or procedural generators.
+//!
+//! The output is canonical: headers are written in a fixed order, rows are written as
+//! `[1, 0, 1],` and comments or blank lines of the original file are not kept. Reading
+//! the written text back with [`parse_map_data`](super::rrm_support::parse_map_data)
+//! always gives a map equal to the one that was written, maps that would be read back
+//! differently are rejected with [`RrmError::Unwritable`].
+
+// --- Imports ---
+use super::error::RrmError;
+use super::map::{Map, DEFAULT_CUBE_SIZE};
+use super::palette::{Palette, WallAppearance};
+use super::rrm_support::HEADER_KEYS;
+use sdl2::pixels::Color;
+use std::path::Path;
+use std::*;
+
+// --- Logic ---
+/// Writes a map as canonical .rrm text.
+///
+/// Only tiles that differ from the default palette are written as `TILE=` headers, since
+/// the loader starts from the default palette. Palettes without the default tiles 0 and 1
+/// are written with a `PALETTE=empty` header followed by all of their tiles.
+///
+/// # Arguments
+///
+/// * `map` - The map to write.
+///
+/// # Returns
+///
+/// * `Result<String, RrmError>` - The contents of the .rrm file, or an error if the map holds
+///   something the loader would read back differently, e.g. a tile that isn't in the palette
+///   or a tile name with a `,` in it.
+pub fn write_map_data(map: &Map) -> Result<String, RrmError> {
+    let mut lines = Vec::new();
+    let metadata = map.metadata();
+
+    if map.width() == 0 || map.height() == 0 {
+        return Err(unwritable("the map must be at least 1 tile wide and high"));
+    }
+    if let Some(name) = &metadata.name {
+        check_text(name, &[], "the name")?;
+        lines.push(format!("NAME={}", name));
+    }
+    if let Some(author) = &metadata.author {
+        check_text(author, &[], "the author")?;
+        lines.push(format!("AUTHOR={}", author));
+    }
+
+    if map.width() == map.height() {
+        lines.push(format!("SIZE={}", map.width()));
+    } else {
+        lines.push(format!("SIZE={}x{}", map.width(), map.height()));
+    }
+
+    if map.cube_size() != DEFAULT_CUBE_SIZE {
+        lines.push(format!("CUBE_SIZE={}", map.cube_size()));
+    }
+    if let Some(start) = &metadata.player_start {
+        check_finite(&[start.x, start.y, start.angle], "the player start")?;
+        lines.push(format!(
+            "PLAYER_START={},{},{}",
+            start.x, start.y, start.angle
+        ));
+    }
+    if let Some(color) = metadata.ceiling_color {
+        lines.push(format!("CEILING_COLOR={}", format_color(color)));
+    }
+    if let Some(color) = metadata.floor_color {
+        lines.push(format!("FLOOR_COLOR={}", format_color(color)));
+    }
+    if let Some(fog) = &metadata.fog {
+        if !fog.is_valid() {
+            return Err(unwritable(&format!(
+                "the fog from {} to {} must start at 0 or later and end after it",
+                fog.start, fog.end
+            )));
+        }
+        lines.push(format!(
+            "FOG={},{},{}",
+            format_color(fog.color),
+            fog.start,
+            fog.end
+        ));
+    }
+
+    // The loader can't remove default tiles, so start from an empty palette if any is missing
+    let default_palette = Palette::default();
+    let empty_palette = default_palette
+        .iter()
+        .any(|tile| !map.palette().contains(tile.id));
+    if empty_palette {
+        lines.push(String::from("PALETTE=empty"));
+    }
+    for tile in map.palette().iter() {
+        if !empty_palette && default_palette.get(tile.id) == Some(tile) {
+            continue;
+        }
+
+        check_text(&tile.name, &[','], &format!("the name of tile {}", tile.id))?;
+        let what = format!("the wall of tile {}", tile.id);
+        let mut line = format!(
+            "TILE={},{},{},{},{}",
+            tile.id,
+            tile.name,
+            if tile.solid { "solid" } else { "passable" },
+            format_appearance(&tile.wall, &what)?,
+            format_color(tile.map_color)
+        );
+        if let Some(floor) = &tile.floor {
+            let what = format!("the floor of tile {}", tile.id);
+            line.push_str(&format!(",{}", format_appearance(floor, &what)?));
+        }
+        lines.push(line);
+    }
+
+    for (i, spawn) in map.entity_spawns().iter().enumerate() {
+        let what = format!("the name of entity {}", i);
+        check_text(&spawn.name, &[','], &what)?;
+        if spawn.name.is_empty() {
+            return Err(unwritable(&format!("{} is empty", what)));
+        }
+        if map.entity_spawns()[..i]
+            .iter()
+            .any(|other| other.name == spawn.name)
+        {
+            return Err(unwritable(&format!(
+                "more than one entity is named {:?}",
+                spawn.name
+            )));
+        }
+        check_finite(
+            &[spawn.x, spawn.y, spawn.angle, spawn.speed, spawn.health],
+            &format!("entity {:?}", spawn.name),
+        )?;
+
+        lines.push(format!(
+            "ENTITY={},{},{},{},{},{},{},{}",
+            spawn.name,
+            spawn.kind.as_str(),
+            spawn.x,
+            spawn.y,
+            spawn.angle,
+            spawn.speed,
+            spawn.health,
+            spawn.sprite_id
+        ));
+    }
+
+    for (key, value) in &metadata.extra {
+        check_text(key, &['='], "the header key")?;
+        if HEADER_KEYS.contains(&key.as_str()) || key.starts_with('#') {
+            return Err(unwritable(&format!(
+                "the extra header {:?} would be read back as a known header or a comment",
+                key
+            )));
+        }
+        check_text(value, &[], &format!("the value of the {:?} header", key))?;
+        lines.push(format!("{}={}", key, value));
+    }
+
+    lines.push(String::new());
+    for (y, row) in map.data().chunks(map.width()).enumerate() {
+        if let Some(x) = row.iter().position(|&tile| !map.palette().contains(tile)) {
+            return Err(unwritable(&format!(
+                "tile {} at x {}, y {} isn't in the palette",
+                row[x], x, y
+            )));
+        }
+
+        let tiles = row
+            .iter()
+            .map(|tile| tile.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        lines.push(format!("[{}],", tiles));
+    }
+
+    lines.push(String::new());
+    Ok(lines.join("\n"))
+}
+
+/// Writes a map to a .rrm file.
+///
+/// # Arguments
+///
+/// * `map` - The map to write.
+/// * `path_to_file` - A reference to a Path that points to the file to write.
+///
+/// # Returns
+///
+/// * `Result<(), RrmError>` - Ok(()) if successful, or an error if the map can't be written
+///   as .rrm text or the file can't be written.
+pub fn save_map(map: &Map, path_to_file: &Path) -> Result<(), RrmError> {
+    let data = write_map_data(map).map_err(|error| match error {
+        RrmError::Unwritable { message, .. } => RrmError::Unwritable {
+            path: path_to_file.to_path_buf(),
+            message,
+        },
+        error => error,
+    })?;
+
+    fs::write(path_to_file, data).map_err(|source| RrmError::Io {
+        path: path_to_file.to_path_buf(),
+        source,
+    })
+}
+
+/// Returns an error if `text` can't be written as a header field and read back unchanged.
+///
+/// Line breaks and whitespace around the text are never allowed, `forbidden` lists the
+/// characters the loader splits the header at.
+fn check_text(text: &str, forbidden: &[char], what: &str) -> Result<(), RrmError> {
+    if let Some(c) = text
+        .chars()
+        .find(|c| *c == '\n' || *c == '\r' || forbidden.contains(c))
+    {
+        return Err(unwritable(&format!(
+            "{} {:?} can't contain {:?}",
+            what, text, c
+        )));
+    }
+    if text.trim() != text {
+        return Err(unwritable(&format!(
+            "{} {:?} can't start or end with whitespace",
+            what, text
+        )));
+    }
+    Ok(())
+}
+
+/// Returns an error if one of the numbers of a header is NaN or infinite, since NaN is
+/// never equal to itself after reading it back.
+fn check_finite(values: &[f32], what: &str) -> Result<(), RrmError> {
+    match values.iter().find(|value| !value.is_finite()) {
+        Some(value) => Err(unwritable(&format!(
+            "{} holds {}, expected a finite number",
+            what, value
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Returns an [`RrmError::Unwritable`] error that isn't tied to a file yet.
+fn unwritable(message: &str) -> RrmError {
+    RrmError::Unwritable {
+        path: path::PathBuf::new(),
+        message: message.to_string(),
+    }
+}
+
+/// Formats the wall or floor of a tile as a colour or a texture path.
+///
+/// Texture paths the loader would read back as a colour or split into several fields
+/// are rejected.
+fn format_appearance(appearance: &WallAppearance, what: &str) -> Result<String, RrmError> {
+    match appearance {
+        WallAppearance::Color(color) => Ok(format_color(*color)),
+        WallAppearance::Texture(path) => {
+            check_text(path, &[','], &format!("the texture path of {}", what))?;
+            if path.is_empty() || path.starts_with('#') {
+                return Err(unwritable(&format!(
+                    "the texture path of {} {:?} must not be empty or start with `#`",
+                    what, path
+                )));
+            }
+            Ok(path.clone())
+        }
+    }
+}
+
+/// Formats a colour as `#RRGGBB`, or as `#RRGGBBAA` if it isn't opaque.
+fn format_color(color: Color) -> String {
+    if color.a == 255 {
+        format!("#{:02X}{:02X}{:02X}", color.r, color.g, color.b)
+    } else {
+        format!(
+            "#{:02X}{:02X}{:02X}{:02X}",
+            color.r, color.g, color.b, color.a
+        )
+    }
+}
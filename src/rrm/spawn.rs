@@ -0,0 +1,52 @@
+//! # Entity Spawn Module
+//!
+//! This module provides the entity layer of a map. Every `ENTITY=` header places one entity:
+//!
+//! ```rrm
+//! ENTITY=guard_1,hostile,3.5,2.5,90,0.5,20,1
+//! ENTITY=cat,neutral,5.5,6.5,0,0.25,5,2
+//! ```
+//!
+//! The fields are the unique name, `hostile` or `neutral`, the x- and y-coordinate in tiles,
+//! the angle in degrees, the speed, the health and the sprite id.
+//! Use [`LevelEntities`](crate::entities::LevelEntities) to turn them into entities.
+
+/// The kind of entity to spawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityKind {
+    /// Spawns a [`HostileEntity`](crate::entities::HostileEntity).
+    Hostile,
+    /// Spawns a [`NeutralEntity`](crate::entities::NeutralEntity).
+    Neutral,
+}
+
+impl EntityKind {
+    /// Returns the name of the kind as written in map files.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntityKind::Hostile => "hostile",
+            EntityKind::Neutral => "neutral",
+        }
+    }
+}
+
+/// An entity placed in a map.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntitySpawn {
+    /// The unique name of the entity.
+    pub name: String,
+    /// The kind of entity.
+    pub kind: EntityKind,
+    /// The x-coordinate in tiles.
+    pub x: f32,
+    /// The y-coordinate in tiles.
+    pub y: f32,
+    /// The rotation angle in degrees.
+    pub angle: f32,
+    /// The movement speed.
+    pub speed: f32,
+    /// The health points.
+    pub health: f32,
+    /// The ID for sprite rendering.
+    pub sprite_id: u32,
+}
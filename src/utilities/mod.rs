@@ -1,3 +1,4 @@
 pub mod opengl;
+pub(crate) mod png;
 
-pub use opengl::{clear_screen, setup_viewport};
+pub use opengl::{clear_screen, draw_image, draw_image_scaled, setup_viewport};
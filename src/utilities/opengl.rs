@@ -1,3 +1,4 @@
+use crate::rendering::Image;
 use glu_sys::*;
 
 /// Sets up the OpenGL viewport.
@@ -28,3 +29,52 @@ pub unsafe fn clear_screen() {
     glClearColor(0.0, 0.0, 0.0, 1.0);
     glClear(GL_COLOR_BUFFER_BIT);
 }
+
+/// Draws an image to the top-left corner of the viewport, one pixel per screen pixel.
+///
+/// # Safety
+///
+/// This function uses unsafe OpenGL calls and should be used carefully. It expects the
+/// projection set up by [`setup_viewport`].
+///
+/// # Arguments
+///
+/// * `image` - The image to draw.
+pub unsafe fn draw_image(image: &Image) {
+    draw_image_scaled(image, 0, 0, image.width as i32, image.height as i32);
+}
+
+/// Draws an image stretched over a rectangle of the viewport, using the nearest pixel.
+///
+/// # Safety
+///
+/// This function uses unsafe OpenGL calls and should be used carefully. It expects the
+/// projection set up by [`setup_viewport`].
+///
+/// # Arguments
+///
+/// * `image` - The image to draw.
+/// * `x` - The x-coordinate of the top-left corner in screen pixels.
+/// * `y` - The y-coordinate of the top-left corner in screen pixels.
+/// * `width` - The width to stretch the image to.
+/// * `height` - The height to stretch the image to.
+pub unsafe fn draw_image_scaled(image: &Image, x: i32, y: i32, width: i32, height: i32) {
+    if image.width == 0 || image.height == 0 {
+        return;
+    }
+
+    // Rows are stored top to bottom, so draw them downwards from the top edge
+    glRasterPos2i(x, y);
+    glPixelZoom(
+        width as f32 / image.width as f32,
+        -(height as f32 / image.height as f32),
+    );
+    glDrawPixels(
+        image.width as GLsizei,
+        image.height as GLsizei,
+        GL_RGBA,
+        GL_UNSIGNED_BYTE,
+        image.pixels.as_ptr().cast(),
+    );
+    glPixelZoom(1.0, 1.0);
+}
@@ -0,0 +1,74 @@
+//! # PNG Module
+//!
+//! This module reads and writes [`Image`]s as PNG files with the `png` crate, for screenshots,
+//! PNG textures and reference images in tests.
+//!
+//! Images are written as 8-bit RGBA. Every PNG the `png` crate can read is supported, pixels in
+//! other colour types and bit depths are converted to 8-bit RGBA.
+
+use crate::rendering::Image;
+use std::*;
+
+/// Encodes an image as a PNG file.
+///
+/// # Arguments
+///
+/// * `image` - The image to encode.
+///
+/// # Returns
+///
+/// * `Result<Vec<u8>, String>` - The bytes of the PNG file, or an error if the image can't be
+///   encoded, e.g. because it is empty.
+pub(crate) fn encode_png(image: &Image) -> Result<Vec<u8>, String> {
+    let mut data = Vec::new();
+    let mut encoder = ::png::Encoder::new(&mut data, image.width, image.height);
+    encoder.set_color(::png::ColorType::Rgba);
+    encoder.set_depth(::png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer
+        .write_image_data(&image.pixels)
+        .map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| e.to_string())?;
+
+    Ok(data)
+}
+
+/// Decodes a PNG file.
+///
+/// # Arguments
+///
+/// * `data` - The bytes of the PNG file.
+///
+/// # Returns
+///
+/// * `Result<Image, String>` - The image as RGBA, or an error if the file is broken.
+pub(crate) fn decode_png(data: &[u8]) -> Result<Image, String> {
+    let mut decoder = ::png::Decoder::new(data);
+    // Expand palettes, transparency and low bit depths and strip 16-bit channels to 8 bits
+    decoder.set_transformations(::png::Transformations::normalize_to_color8());
+
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut buffer).map_err(|e| e.to_string())?;
+    let samples = &buffer[..frame.buffer_size()];
+
+    let pixels = match frame.color_type {
+        ::png::ColorType::Rgba => samples.to_vec(),
+        ::png::ColorType::Rgb => samples
+            .chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+            .collect(),
+        ::png::ColorType::GrayscaleAlpha => samples
+            .chunks_exact(2)
+            .flat_map(|ga| [ga[0], ga[0], ga[0], ga[1]])
+            .collect(),
+        ::png::ColorType::Grayscale => samples
+            .iter()
+            .flat_map(|&gray| [gray, gray, gray, 255])
+            .collect(),
+        ::png::ColorType::Indexed => return Err("unexpanded palette image".to_string()),
+    };
+
+    Ok(Image::new(frame.width, frame.height, pixels))
+}
@@ -7,14 +7,15 @@
 //! The window module is structured with the following components:
 //!
 //! - [`SdlWindow`]: The main window implementation using SDL2 and OpenGL
+//! - [`FrameRecorder`]: Saves a range of frames as a numbered PNG sequence
 //!
 //! ## Usage
 //!
 //! The main type you'll interact with is [`SdlWindow`], which provides window management
 //! and OpenGL context handling.
 //!
-//! ```rust
-//! use your_engine_name::window::SdlWindow;
+//! ```rust,no_run
+//! use rust_ray::window::SdlWindow;
 //!
 //! fn main() -> Result<(), String> {
 //!     // Create a new window
@@ -32,11 +33,15 @@
 //! - Event pump integration
 //! - VSync support
 //! - Mouse grab functionality
+//! - Screenshots and PNG sequence recording
 //!
 //! ## Modules
 //!
+//! - [`recorder`]: Contains the frame recorder
 //! - [`sdl_window`]: Contains the SDL2-based window implementation
 
+pub mod recorder;
 pub mod sdl_window;
 
+pub use recorder::FrameRecorder;
 pub use sdl_window::SdlWindow;
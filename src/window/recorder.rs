@@ -0,0 +1,118 @@
+//! # Recorder Module
+//!
+//! This module provides the [`FrameRecorder`], which saves a range of frames as a numbered
+//! sequence of PNG files, e.g. `frame_000120.png`, `frame_000121.png` and so on. Sequences
+//! can be turned into videos with tools like ffmpeg:
+//! `ffmpeg -framerate 60 -i frame_%06d.png trailer.mp4`.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use rust_ray::window::{FrameRecorder, SdlWindow};
+//!
+//! fn main() -> Result<(), String> {
+//!     let window = SdlWindow::new("Game Window", 1280, 720)?;
+//!
+//!     // Record the frames 60 to 179, two seconds at 60 fps
+//!     let mut recorder = FrameRecorder::new("recording", 60..180);
+//!     while !recorder.is_finished() {
+//!         // Render frame...
+//!         recorder.record(&window)?;
+//!         window.swap_window();
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use super::sdl_window::SdlWindow;
+use crate::rendering::Image;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::*;
+
+/// Saves the frames in a range as numbered PNG files.
+///
+/// The recorder counts the frames it is given from 0, and only the frames in its range are
+/// read back and saved, so it can be kept running for a whole session.
+#[derive(Debug, Clone)]
+pub struct FrameRecorder {
+    /// The directory the frames are saved in, it is created if it doesn't exist.
+    pub directory: PathBuf,
+    /// The start of the file names, followed by the frame number.
+    pub prefix: String,
+    /// The frames that are saved.
+    pub frames: Range<u64>,
+    frame: u64,
+}
+
+impl FrameRecorder {
+    /// Creates a recorder saving frames as `frame_000000.png` and so on.
+    ///
+    /// # Arguments
+    ///
+    /// * `directory` - The directory the frames are saved in.
+    /// * `frames` - The frames that are saved, counted from 0.
+    pub fn new(directory: impl Into<PathBuf>, frames: Range<u64>) -> Self {
+        Self {
+            directory: directory.into(),
+            prefix: "frame_".to_string(),
+            frames,
+            frame: 0,
+        }
+    }
+
+    /// Returns the number of the next frame.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Returns true once every frame in the range has been passed.
+    pub fn is_finished(&self) -> bool {
+        self.frame >= self.frames.end
+    }
+
+    /// Returns the path the frame with a number is saved to.
+    pub fn frame_path(&self, frame: u64) -> PathBuf {
+        self.directory
+            .join(format!("{}{:06}.png", self.prefix, frame))
+    }
+
+    /// Records the frame that is currently being rendered in a window.
+    ///
+    /// Call this once per frame, after rendering and before [`SdlWindow::swap_window`].
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<PathBuf>, String>` - The path the frame was saved to, `None` if it is
+    ///   outside of the range, or an error if the file can't be written.
+    pub fn record(&mut self, window: &SdlWindow) -> Result<Option<PathBuf>, String> {
+        if !self.frames.contains(&self.frame) {
+            self.frame += 1;
+            return Ok(None);
+        }
+
+        self.record_image(&window.capture_frame())
+    }
+
+    /// Records a frame that has been rendered into an image, e.g. by the software renderer.
+    ///
+    /// Call this once per frame.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<PathBuf>, String>` - The path the frame was saved to, `None` if it is
+    ///   outside of the range, or an error if the file can't be written.
+    pub fn record_image(&mut self, frame: &Image) -> Result<Option<PathBuf>, String> {
+        let number = self.frame;
+        self.frame += 1;
+        if !self.frames.contains(&number) {
+            return Ok(None);
+        }
+
+        fs::create_dir_all(&self.directory)
+            .map_err(|e| format!("{}: {}", self.directory.display(), e))?;
+        let path = self.frame_path(number);
+        frame.save_png(&path)?;
+        Ok(Some(path))
+    }
+}
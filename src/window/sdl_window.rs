@@ -9,11 +9,12 @@
 //! - VSync support
 //! - Mouse grab functionality
 //! - Event handling through SDL2
+//! - Frame capture and PNG screenshots
 //!
 //! ## Example
 //!
-//! ```rust
-//! use your_engine_name::window::SdlWindow;
+//! ```rust,no_run
+//! use rust_ray::window::SdlWindow;
 //!
 //! fn main() -> Result<(), String> {
 //!     let width = 800;
@@ -25,10 +26,17 @@
 
 extern crate sdl2;
 
+use crate::rendering::renderer::{bind_context, release_context};
+use crate::rendering::Image;
 use sdl2::{
     video::{GLContext, SwapInterval, Window},
     EventPump,
 };
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Constants
+static NEXT_CONTEXT_ID: AtomicU64 = AtomicU64::new(0); // Id of the next OpenGL context of a window
 
 /// A window implementation using SDL2 with OpenGL context.
 ///
@@ -40,12 +48,13 @@ use sdl2::{
 ///
 /// * `window` - The underlying SDL window instance
 /// * `event_pump` - SDL event pump for handling window and input events
-/// * `_gl_context` - OpenGL context (kept alive through struct ownership)
+/// * `gl_context` - OpenGL context (kept alive through struct ownership)
+/// * `context_id` - Id of the OpenGL context, so the renderer can tell contexts apart
 ///
 /// # Example
 ///
-/// ```rust
-/// use your_engine_name::window::SdlWindow;
+/// ```rust,no_run
+/// use rust_ray::window::SdlWindow;
 ///
 /// fn init_window() -> Result<SdlWindow, String> {
 ///     let window = SdlWindow::new("Game Window", 800, 600)?;
@@ -57,7 +66,8 @@ pub struct SdlWindow {
     pub window: Window,
     /// The SDL event pump for handling events.
     pub event_pump: EventPump,
-    _gl_context: GLContext, // Keep the context alive as long as the window exists
+    gl_context: GLContext, // Keep the context alive as long as the window exists
+    context_id: u64,
 }
 
 impl SdlWindow {
@@ -80,10 +90,13 @@ impl SdlWindow {
     ///
     /// # Examples
     ///
-    /// ```rust
-    /// use your_engine_name::window::SdlWindow;
+    /// ```rust,no_run
+    /// use rust_ray::window::SdlWindow;
     ///
+    /// # fn main() -> Result<(), String> {
     /// let window = SdlWindow::new("Game Window", 1920, 1080)?;
+    /// # Ok(())
+    /// # }
     /// ```
     ///
     /// # Errors
@@ -101,6 +114,7 @@ impl SdlWindow {
         gl_attr.set_context_profile(sdl2::video::GLProfile::Compatibility);
         gl_attr.set_context_version(4, 3);
         gl_attr.set_double_buffer(true);
+        gl_attr.set_depth_size(24);
 
         let mut window = video_subsystem
             .window(title, width, height)
@@ -118,15 +132,35 @@ impl SdlWindow {
 
         gl::load_with(|name| video_subsystem.gl_get_proc_address(name) as *const _);
 
+        let context_id = NEXT_CONTEXT_ID.fetch_add(1, Ordering::Relaxed);
+        bind_context(context_id, unsafe { sdl2::sys::SDL_GL_GetCurrentContext() });
+
         let event_pump = sdl.event_pump()?;
 
         Ok(SdlWindow {
             window,
             event_pump,
-            _gl_context: gl_context,
+            gl_context,
+            context_id,
         })
     }
 
+    /// Makes the window's OpenGL context current, so the next frames are rendered into it.
+    ///
+    /// Every window makes its context current when it is created, so this is only needed
+    /// when rendering into several windows in turn.
+    ///
+    /// # Returns
+    ///
+    /// Returns an error message if the context can't be made current.
+    pub fn make_current(&self) -> Result<(), String> {
+        self.window.gl_make_current(&self.gl_context)?;
+        bind_context(self.context_id, unsafe {
+            sdl2::sys::SDL_GL_GetCurrentContext()
+        });
+        Ok(())
+    }
+
     /// Swaps the window's OpenGL buffers.
     ///
     /// This method should be called after rendering each frame to display
@@ -135,7 +169,8 @@ impl SdlWindow {
     ///
     /// # Example
     ///
-    /// ```rust
+    /// ```rust,no_run
+    /// # use rust_ray::window::SdlWindow;
     /// fn render_loop(window: &SdlWindow) {
     ///     // Render frame...
     ///     window.swap_window();
@@ -144,6 +179,109 @@ impl SdlWindow {
     pub fn swap_window(&self) {
         self.window.gl_swap_window();
     }
+
+    /// Reads back the frame that is currently being rendered.
+    ///
+    /// This method reads the back buffer, so it has to be called after rendering and before
+    /// [`SdlWindow::swap_window`], which leaves the back buffer undefined.
+    ///
+    /// # Returns
+    ///
+    /// The frame as an RGBA image the size of the drawable area of the window.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use rust_ray::window::SdlWindow;
+    /// fn render_loop(window: &SdlWindow) {
+    ///     // Render frame...
+    ///     let frame = window.capture_frame();
+    ///     println!("captured {}x{} pixels", frame.width, frame.height);
+    ///     window.swap_window();
+    /// }
+    /// ```
+    pub fn capture_frame(&self) -> Image {
+        let (width, height) = self.window.drawable_size();
+        let row_length = width as usize * 4;
+        let mut pixels = vec![0u8; row_length * height as usize];
+
+        unsafe {
+            gl::ReadBuffer(gl::BACK);
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::ReadPixels(
+                0,
+                0,
+                width as i32,
+                height as i32,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixels.as_mut_ptr().cast(),
+            );
+        }
+
+        // OpenGL returns the rows from the bottom up
+        let mut flipped = Vec::with_capacity(pixels.len());
+        for row in pixels.chunks(row_length.max(1)).rev() {
+            flipped.extend_from_slice(row);
+        }
+
+        let mut image = Image::new(width, height, flipped);
+        for pixel in image.pixels.chunks_exact_mut(4) {
+            pixel[3] = 255; // The window is opaque, whatever alpha was rendered
+        }
+        image
+    }
+
+    /// Saves the frame that is currently being rendered as a PNG file.
+    ///
+    /// Like [`SdlWindow::capture_frame`], this has to be called after rendering and before
+    /// [`SdlWindow::swap_window`].
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the PNG file, it is overwritten if it exists.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - An error if the file can't be written.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use rust_ray::window::SdlWindow;
+    /// # use std::path::Path;
+    /// fn render_loop(window: &SdlWindow) -> Result<(), String> {
+    ///     // Render frame...
+    ///     window.save_screenshot(Path::new("screenshot.png"))?;
+    ///     window.swap_window();
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn save_screenshot(&self, path: &Path) -> Result<(), String> {
+        self.capture_frame().save_png(path)
+    }
+}
+
+impl Drop for SdlWindow {
+    /// Deletes the renderer of the window's OpenGL context before the context is destroyed.
+    ///
+    /// The context is made current for that, and the context that was current before is
+    /// made current again afterwards.
+    fn drop(&mut self) {
+        unsafe {
+            let previous_window = sdl2::sys::SDL_GL_GetCurrentWindow();
+            let previous_context = sdl2::sys::SDL_GL_GetCurrentContext();
+
+            let is_current = self.window.gl_make_current(&self.gl_context).is_ok();
+            release_context(self.context_id, is_current);
+
+            // Switch back if another window's context was current
+            let context = sdl2::sys::SDL_GL_GetCurrentContext();
+            if is_current && !previous_context.is_null() && previous_context != context {
+                sdl2::sys::SDL_GL_MakeCurrent(previous_window, previous_context);
+            }
+        }
+    }
 }
 
 /// Checks for OpenGL errors and prints them to stderr.
@@ -158,7 +296,8 @@ impl SdlWindow {
 ///
 /// # Examples
 ///
-/// ```rust
+/// ```rust,no_run
+/// # use rust_ray::check_gl_error;
 /// // After performing OpenGL operations
 /// check_gl_error();
 /// ```
@@ -0,0 +1,32 @@
+use rust_ray::rendering::Image;
+use rust_ray::FrameRecorder;
+use sdl2::pixels::Color;
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn saves_only_the_frames_in_its_range() {
+    let directory = Path::new(env!("CARGO_TARGET_TMPDIR")).join("frame_recorder");
+    let _ = fs::remove_dir_all(&directory);
+    let mut recorder = FrameRecorder::new(&directory, 2..4);
+
+    let mut saved = Vec::new();
+    for frame in 0..6 {
+        let image = Image::filled(4, 3, Color::RGB(frame * 40, 0, 0));
+        if let Some(path) = recorder.record_image(&image).expect("frame is saved") {
+            saved.push((path, image));
+        }
+        assert_eq!(recorder.is_finished(), frame >= 3);
+    }
+
+    let names = saved
+        .iter()
+        .map(|(path, _)| path.file_name().unwrap().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["frame_000002.png", "frame_000003.png"]);
+    assert_eq!(fs::read_dir(&directory).unwrap().count(), 2);
+
+    for (path, image) in saved {
+        assert_eq!(Image::load(&path).expect("frame can be read back"), image);
+    }
+}
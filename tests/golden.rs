@@ -0,0 +1,198 @@
+//! Golden-image tests: a fixed scene is rendered with the software renderer and compared
+//! against the reference images in `tests/golden`.
+//!
+//! Run `UPDATE_GOLDEN=1 cargo test --test golden` to accept intended changes, and check the
+//! new reference images before committing them. On a mismatch the rendered image and a diff
+//! image, with every differing pixel in red, are written to the test's temporary directory.
+
+use rust_ray::entities::LevelEntities;
+use rust_ray::rendering::{
+    debug_render_image, render_2d_image, render_3d_image, Camera, Image, Sprite, SpriteSheet,
+    Textures,
+};
+use rust_ray::rrm::parse_map_data;
+use rust_ray::Map;
+use sdl2::pixels::Color;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How much a colour channel may differ before a pixel counts as different.
+const CHANNEL_TOLERANCE: u8 = 2;
+/// The fraction of pixels that may differ, e.g. from rounding on other platforms.
+const MAX_DIFFERENT_PIXELS: f64 = 0.001;
+
+fn scene() -> Map {
+    parse_map_data(
+        include_str!("golden/scene.rrm"),
+        Path::new("tests/golden/scene.rrm"),
+    )
+    .unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// A brick texture for tile 2 and a sheet with a red and a yellow sprite.
+fn textures() -> Textures {
+    let mut brick = Image::filled(16, 16, Color::RGB(140, 70, 20));
+    for row in (0..16).step_by(4) {
+        brick.fill_rect(0, row, 16, 1, Color::RGB(200, 190, 170));
+        let offset = if row % 8 == 0 { 0 } else { 8 };
+        brick.fill_rect(offset, row, 1, 4, Color::RGB(200, 190, 170));
+    }
+
+    let mut sheet = Image::filled(32, 16, Color::RGBA(0, 0, 0, 0));
+    sheet.fill_rect(4, 2, 8, 14, Color::RGB(200, 40, 40));
+    sheet.fill_rect(20, 8, 10, 8, Color::RGB(230, 200, 40));
+
+    let mut textures = Textures::new();
+    textures.insert_wall(2, brick);
+    textures.set_sprite_sheet(SpriteSheet::new(sheet, 16, 16));
+    textures
+}
+
+fn sprites(map: &Map) -> Vec<Sprite> {
+    LevelEntities::from_map(map).sprites()
+}
+
+fn start_camera(map: &Map) -> Camera {
+    let (x, y, angle) = map.player_start().expect("scene has a player start");
+    Camera::new(x, y, angle)
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.png", name))
+}
+
+/// Compares an image with its reference image, or replaces the reference with
+/// `UPDATE_GOLDEN` set.
+fn assert_golden(name: &str, actual: &Image) {
+    let path = golden_path(name);
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        actual.save_png(&path).unwrap_or_else(|e| panic!("{}", e));
+        return;
+    }
+
+    let expected = Image::load(&path)
+        .unwrap_or_else(|e| panic!("{}\nrun with UPDATE_GOLDEN=1 to create it", e));
+    let (diff, different) = diff_images(&expected, actual);
+    let allowed = (actual.width as f64 * actual.height as f64 * MAX_DIFFERENT_PIXELS) as usize;
+    if expected.width == actual.width && expected.height == actual.height && different <= allowed {
+        return;
+    }
+
+    let out = Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden");
+    fs::create_dir_all(&out).unwrap_or_else(|e| panic!("{}", e));
+    let actual_path = out.join(format!("{}.actual.png", name));
+    let diff_path = out.join(format!("{}.diff.png", name));
+    actual
+        .save_png(&actual_path)
+        .unwrap_or_else(|e| panic!("{}", e));
+    diff.save_png(&diff_path)
+        .unwrap_or_else(|e| panic!("{}", e));
+
+    panic!(
+        "{} doesn't match {}: {} of {}x{} pixels differ (expected {}x{}, at most {} may differ)\n\
+         rendered: {}\ndiff: {}",
+        name,
+        path.display(),
+        different,
+        actual.width,
+        actual.height,
+        expected.width,
+        expected.height,
+        allowed,
+        actual_path.display(),
+        diff_path.display()
+    );
+}
+
+/// Returns an image of the differences and the number of differing pixels.
+///
+/// The diff image is the rendered image in dark grey, with every differing pixel in red and
+/// every pixel outside of the reference image in blue.
+fn diff_images(expected: &Image, actual: &Image) -> (Image, usize) {
+    let mut diff = Image::filled(actual.width, actual.height, Color::RGB(0, 0, 0));
+    let mut different = 0;
+
+    for y in 0..actual.height {
+        for x in 0..actual.width {
+            let a = actual.pixel(x, y).expect("pixel is inside the image");
+            let color = match expected.pixel(x, y) {
+                Some(e) => {
+                    let channels = [(a.r, e.r), (a.g, e.g), (a.b, e.b), (a.a, e.a)];
+                    if channels
+                        .iter()
+                        .any(|&(a, e)| a.abs_diff(e) > CHANNEL_TOLERANCE)
+                    {
+                        different += 1;
+                        Color::RGB(255, 0, 0)
+                    } else {
+                        let grey = ((a.r as u32 + a.g as u32 + a.b as u32) / 9) as u8;
+                        Color::RGB(grey, grey, grey)
+                    }
+                }
+                None => {
+                    different += 1;
+                    Color::RGB(0, 0, 255)
+                }
+            };
+            diff.set_pixel(x, y, color);
+        }
+    }
+
+    (diff, different)
+}
+
+#[test]
+fn view_2d_matches_golden() {
+    let map = scene();
+    let mut frame = Image::filled(640, 512, Color::RGB(0, 0, 0));
+    render_2d_image(&map, &start_camera(&map), &mut frame);
+
+    assert_golden("view_2d", &frame);
+}
+
+#[test]
+fn view_3d_matches_golden() {
+    let map = scene();
+    let mut frame = Image::filled(320, 200, Color::RGB(0, 0, 0));
+    render_3d_image(
+        &map,
+        &textures(),
+        &sprites(&map),
+        &start_camera(&map),
+        &mut frame,
+    );
+
+    assert_golden("view_3d", &frame);
+}
+
+#[test]
+fn wide_pitched_view_3d_matches_golden() {
+    let map = scene();
+    let (x, y) = map.tile_to_world(2.5, 6.5);
+    let mut camera = Camera::new(x, y, -30f32.to_radians());
+    camera.fov = 90f32.to_radians();
+    camera.pitch = -0.15;
+
+    let mut frame = Image::filled(320, 200, Color::RGB(0, 0, 0));
+    render_3d_image(&map, &textures(), &sprites(&map), &camera, &mut frame);
+
+    assert_golden("view_3d_wide_pitched", &frame);
+}
+
+#[test]
+fn debug_view_matches_golden() {
+    let map = scene();
+    let mut frame = Image::filled(640, 512, Color::RGB(0, 0, 0));
+    debug_render_image(
+        &map,
+        &textures(),
+        &sprites(&map),
+        &start_camera(&map),
+        &mut frame,
+    );
+
+    assert_golden("debug_view", &frame);
+}
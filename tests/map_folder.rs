@@ -0,0 +1,24 @@
+use rust_ray::rrm::{list_maps, load_map_from};
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn only_map_files_are_listed() {
+    let folder = Path::new(env!("CARGO_TARGET_TMPDIR")).join("map_folder");
+    let _ = fs::remove_dir_all(&folder);
+    fs::create_dir_all(folder.join("folder.rrm")).unwrap();
+    fs::write(folder.join("LOUD.RRM"), "SIZE=1\n[1],\n").unwrap();
+    fs::write(folder.join("quiet.rrm"), "SIZE=1\n[1],\n").unwrap();
+    fs::write(folder.join("notes.txt"), "not a map").unwrap();
+
+    let folder = folder.to_str().unwrap();
+    let names = list_maps(folder)
+        .unwrap()
+        .into_iter()
+        .map(|file_info| file_info.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["LOUD.RRM", "quiet.rrm"]);
+
+    assert!(load_map_from(folder, "LOUD").is_ok());
+    assert!(load_map_from(folder, "folder").is_err());
+}
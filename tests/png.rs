@@ -0,0 +1,58 @@
+use rust_ray::rendering::Image;
+use std::fs;
+use std::path::Path;
+
+/// Writes a PNG in the given colour type with the `png` crate and loads it as an image.
+fn load(name: &str, color: png::ColorType, palette: Option<(&[u8], &[u8])>, data: &[u8]) -> Image {
+    let directory = Path::new(env!("CARGO_TARGET_TMPDIR")).join("png");
+    fs::create_dir_all(&directory).unwrap();
+    let path = directory.join(format!("{}.png", name));
+
+    let mut file = Vec::new();
+    let mut encoder = png::Encoder::new(&mut file, 2, 1);
+    encoder.set_color(color);
+    encoder.set_depth(png::BitDepth::Eight);
+    if let Some((palette, transparency)) = palette {
+        encoder.set_palette(palette);
+        encoder.set_trns(transparency);
+    }
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(data).unwrap();
+    writer.finish().unwrap();
+    fs::write(&path, file).unwrap();
+
+    Image::load(&path).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[test]
+fn other_colour_types_load_as_rgba() {
+    let expected = [10, 20, 30, 255, 40, 50, 60, 128];
+
+    let rgb = load("rgb", png::ColorType::Rgb, None, &[10, 20, 30, 40, 50, 60]);
+    assert_eq!(rgb.pixels, [10, 20, 30, 255, 40, 50, 60, 255]);
+
+    let gray = load(
+        "gray",
+        png::ColorType::GrayscaleAlpha,
+        None,
+        &[7, 255, 9, 0],
+    );
+    assert_eq!(gray.pixels, [7, 7, 7, 255, 9, 9, 9, 0]);
+
+    let palette = [40, 50, 60, 10, 20, 30];
+    let indexed = load(
+        "indexed",
+        png::ColorType::Indexed,
+        Some((&palette, &[128, 255])),
+        &[1, 0],
+    );
+    assert_eq!(indexed.pixels, expected);
+}
+
+#[test]
+fn broken_files_are_rejected() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("broken.png");
+    fs::write(&path, b"\x89PNG\r\n\x1a\nnot a png").unwrap();
+
+    assert!(Image::load(&path).is_err());
+}
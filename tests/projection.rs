@@ -0,0 +1,115 @@
+use rust_ray::rendering::{cast_wall, cast_walls, cast_walls_into, Camera};
+use rust_ray::Map;
+use std::f32::consts::PI;
+
+const EPSILON: f32 = 1e-2;
+const SCREEN_WIDTH: i32 = 320;
+const SCREEN_HEIGHT: i32 = 200;
+
+/// A wide, empty room, so a camera in it sees nothing but the north wall.
+fn hall() -> Map {
+    let (width, height) = (24, 8);
+    let data = (0..width * height)
+        .map(|i| {
+            let (x, y) = (i % width, i / width);
+            u8::from(x == 0 || y == 0 || x == width - 1 || y == height - 1)
+        })
+        .collect();
+    Map::new(width, height, data)
+}
+
+/// A camera in the middle of the hall looking straight at the north wall.
+fn facing_north_wall(map: &Map) -> Camera {
+    let (x, y) = map.tile_to_world(12.0, 5.5);
+    let mut camera = Camera::new(x, y, -PI / 2.0);
+    camera.fov = 90f32.to_radians();
+    camera
+}
+
+#[test]
+fn flat_wall_has_constant_height_across_all_columns() {
+    let map = hall();
+    let walls = cast_walls(&map, &facing_north_wall(&map), SCREEN_WIDTH, SCREEN_HEIGHT);
+    assert_eq!(walls.len(), SCREEN_WIDTH as usize);
+
+    let expected = SCREEN_HEIGHT as f32 / 4.5;
+    for (column, wall) in walls.iter().enumerate() {
+        let wall = wall.expect("every column sees the wall");
+        assert_eq!(wall.hit.cell_y, 0, "column {} hit another wall", column);
+        assert!(
+            (wall.height() - expected).abs() < EPSILON,
+            "column {} is {} rows high, expected {}",
+            column,
+            wall.height(),
+            expected
+        );
+    }
+}
+
+#[test]
+fn columns_are_evenly_spaced_along_a_flat_wall() {
+    let map = hall();
+    let walls = cast_walls(&map, &facing_north_wall(&map), SCREEN_WIDTH, SCREEN_HEIGHT);
+    let xs = walls
+        .iter()
+        .map(|wall| wall.expect("every column sees the wall").hit.x)
+        .collect::<Vec<_>>();
+
+    // 90 degrees at 4.5 tiles away show 9 tiles of wall
+    let expected = 9.0 * map.cube_size() / SCREEN_WIDTH as f32;
+    for pair in xs.windows(2) {
+        assert!((pair[1] - pair[0] - expected).abs() < EPSILON);
+    }
+}
+
+#[test]
+fn projecting_a_hit_point_gives_back_its_column() {
+    let map = hall();
+    let camera = facing_north_wall(&map);
+    let walls = cast_walls(&map, &camera, SCREEN_WIDTH, SCREEN_HEIGHT);
+
+    for (column, wall) in walls.iter().enumerate() {
+        let wall = wall.expect("every column sees the wall");
+        let (projected, distance) = camera
+            .project(wall.hit.x, wall.hit.y, SCREEN_WIDTH as usize)
+            .expect("in front of the camera");
+        assert!((projected - (column as f32 + 0.5)).abs() < EPSILON);
+        assert!((distance - wall.distance).abs() < EPSILON);
+    }
+}
+
+#[test]
+fn parallel_casting_matches_serial_casting() {
+    let map = Map::default();
+    let mut walls = vec![None; 1920];
+
+    for step in 0..8 {
+        let (x, y) = map.tile_to_world(1.5 + step as f32 * 0.6, 3.5);
+        let camera = Camera::new(x, y, step as f32 * 0.8);
+        cast_walls_into(&map, &camera, 1080, &mut walls);
+
+        let serial = (0..walls.len())
+            .map(|column| cast_wall(&map, &camera, column, walls.len(), 1080))
+            .collect::<Vec<_>>();
+        assert_eq!(walls, serial);
+    }
+}
+
+#[test]
+fn out_of_range_fov_and_near_clip_are_clamped() {
+    let map = hall();
+    let mut camera = facing_north_wall(&map);
+    camera.fov = PI;
+    camera.near_clip = 0.0;
+
+    // The view isn't mirrored and the rays still sweep from left to right
+    let left = camera.ray_angle(0, SCREEN_WIDTH as usize);
+    let right = camera.ray_angle(SCREEN_WIDTH as usize - 1, SCREEN_WIDTH as usize);
+    assert!(left.is_finite() && right.is_finite());
+    assert!(left < camera.angle && camera.angle < right);
+
+    // A camera inside a wall still gets walls of finite height
+    camera.x = 0.0;
+    let walls = cast_walls(&map, &camera, SCREEN_WIDTH, SCREEN_HEIGHT);
+    assert!(walls.iter().flatten().all(|wall| wall.height().is_finite()));
+}
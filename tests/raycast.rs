@@ -0,0 +1,103 @@
+use rust_ray::rendering::{raycast, WallSide};
+use rust_ray::Map;
+use std::f32::consts::PI;
+
+const EPSILON: f32 = 1e-3;
+
+/// A 5x5 room with a pillar of tile 2 in the middle.
+fn room() -> Map {
+    #[rustfmt::skip]
+    let data = vec![
+        1, 1, 1, 1, 1,
+        1, 0, 0, 0, 1,
+        1, 0, 2, 0, 1,
+        1, 0, 0, 0, 1,
+        1, 1, 1, 1, 1,
+    ];
+    Map::new(5, 5, data)
+}
+
+/// Converts a position in tiles to world units.
+fn world(map: &Map, x: f32, y: f32) -> (f32, f32) {
+    map.tile_to_world(x, y)
+}
+
+#[test]
+fn hits_the_wall_straight_ahead() {
+    let map = room();
+    let hit = raycast(&map, world(&map, 1.5, 1.5), 0.0, f32::INFINITY).expect("hit");
+
+    assert!((hit.distance - 2.5 * map.cube_size()).abs() < EPSILON);
+    assert!((hit.x - 4.0 * map.cube_size()).abs() < EPSILON);
+    assert!((hit.y - 1.5 * map.cube_size()).abs() < EPSILON);
+    assert_eq!((hit.cell_x, hit.cell_y), (4, 1));
+    assert_eq!(hit.tile, 1);
+    assert_eq!(hit.side, WallSide::Vertical);
+    assert_eq!(hit.normal, (-1.0, 0.0));
+}
+
+#[test]
+fn reports_the_tile_and_face_that_was_hit() {
+    let map = room();
+    let hit = raycast(&map, world(&map, 2.5, 3.5), -PI / 2.0, f32::INFINITY).expect("hit");
+
+    assert!((hit.distance - 0.5 * map.cube_size()).abs() < EPSILON);
+    assert_eq!((hit.cell_x, hit.cell_y), (2, 2));
+    assert_eq!(hit.tile, 2);
+    assert_eq!(hit.side, WallSide::Horizontal);
+    assert_eq!(hit.normal, (0.0, 1.0));
+}
+
+#[test]
+fn stops_at_max_distance() {
+    let map = room();
+    let origin = world(&map, 1.5, 1.5);
+
+    assert!(raycast(&map, origin, 0.0, 2.0 * map.cube_size()).is_none());
+    assert!(raycast(&map, origin, 0.0, 3.0 * map.cube_size()).is_some());
+}
+
+#[test]
+fn finds_the_map_from_outside() {
+    let map = room();
+    let hit = raycast(&map, world(&map, -3.0, 1.5), 0.0, f32::INFINITY).expect("hit");
+
+    assert!((hit.distance - 3.0 * map.cube_size()).abs() < EPSILON);
+    assert_eq!((hit.cell_x, hit.cell_y), (0, 1));
+    assert_eq!(hit.normal, (-1.0, 0.0));
+
+    assert!(raycast(&map, world(&map, -3.0, 1.5), PI, f32::INFINITY).is_none());
+    assert!(raycast(&map, world(&map, -3.0, -3.0), PI / 2.0, f32::INFINITY).is_none());
+}
+
+#[test]
+fn leaves_open_maps_without_a_hit() {
+    let map = Map::new(3, 1, vec![0, 0, 0]);
+
+    for i in 0..16 {
+        let angle = i as f32 * PI / 8.0;
+        assert!(raycast(&map, world(&map, 1.5, 0.5), angle, f32::INFINITY).is_none());
+    }
+}
+
+#[test]
+fn offset_runs_left_to_right_along_every_face() {
+    let map = room();
+    let c = map.cube_size();
+
+    // Facing east, the right hand side points down the y-axis
+    let east = raycast(&map, (1.5 * c, 1.25 * c), 0.0, f32::INFINITY).expect("hit");
+    assert!((east.offset - 0.25).abs() < EPSILON);
+
+    // Facing west, the right hand side points up the y-axis
+    let west = raycast(&map, (3.5 * c, 1.25 * c), PI, f32::INFINITY).expect("hit");
+    assert!((west.offset - 0.75).abs() < EPSILON);
+
+    // Facing south, the right hand side points down the x-axis
+    let south = raycast(&map, (1.25 * c, 1.5 * c), PI / 2.0, f32::INFINITY).expect("hit");
+    assert!((south.offset - 0.75).abs() < EPSILON);
+
+    // Facing north, the right hand side points along the x-axis
+    let north = raycast(&map, (1.25 * c, 3.5 * c), -PI / 2.0, f32::INFINITY).expect("hit");
+    assert!((north.offset - 0.25).abs() < EPSILON);
+}
@@ -0,0 +1,51 @@
+use rust_ray::rendering::RenderScale;
+
+#[test]
+fn native_scale_renders_one_column_per_window_pixel() {
+    let scale = RenderScale::native();
+
+    assert_eq!(scale.resolution(1920, 1080), (1920, 1080));
+    assert_eq!(scale.destination(1920, 1080), (0, 0, 1920, 1080));
+    assert!(scale.is_native(1920, 1080));
+}
+
+#[test]
+fn fewer_columns_keep_the_aspect_ratio_of_the_window() {
+    assert_eq!(RenderScale::columns(320).resolution(1920, 1080), (320, 180));
+    assert_eq!(RenderScale::columns(320).resolution(1280, 1024), (320, 256));
+    assert!(!RenderScale::columns(320).is_native(1920, 1080));
+
+    // More columns than the window is wide render at the size of the window
+    assert_eq!(
+        RenderScale::columns(4000).resolution(1920, 1080),
+        (1920, 1080)
+    );
+    assert!(RenderScale::columns(4000).is_native(1920, 1080));
+
+    // Column counts that don't fit into an i32 don't wrap around to negative widths
+    assert_eq!(
+        RenderScale::columns(u32::MAX).resolution(1920, 1080),
+        (1920, 1080)
+    );
+    assert_eq!(
+        RenderScale::columns(i32::MAX as u32 + 1).resolution(1920, 1080),
+        (1920, 1080)
+    );
+}
+
+#[test]
+fn integer_scaling_centers_whole_multiples_in_the_window() {
+    let mut scale = RenderScale::columns(320);
+    scale.integer_scaling = true;
+
+    // 320x180 fits 6 times into 1920x1080
+    assert_eq!(scale.destination(1920, 1080), (0, 0, 1920, 1080));
+
+    // 320x240 fits 3 times into 1024x768, with bars on the left and right
+    assert_eq!(scale.resolution(1024, 768), (320, 240));
+    assert_eq!(scale.destination(1024, 768), (32, 24, 960, 720));
+
+    // Without integer scaling the view is stretched over the whole window
+    scale.integer_scaling = false;
+    assert_eq!(scale.destination(1024, 768), (0, 0, 1024, 768));
+}
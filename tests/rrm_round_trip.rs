@@ -0,0 +1,319 @@
+use rust_ray::rrm::{
+    parse_map_data, write_map_data, EntityKind, EntitySpawn, Fog, Map, Palette, PlayerStart,
+    RrmError, Tile, WallAppearance,
+};
+use sdl2::pixels::Color;
+use std::path::Path;
+
+/// Returns the sample map.rrm from the README, so the documented example stays loadable.
+fn readme_sample() -> String {
+    let readme = include_str!("../README.md");
+    let start = readme
+        .find("map.rrm\n\n```\n")
+        .expect("README has a map.rrm sample")
+        + 12;
+    let end = start
+        + readme[start..]
+            .find("```")
+            .expect("map.rrm sample is closed");
+    readme[start..end].to_string()
+}
+
+fn parse(source: &str) -> Map {
+    parse_map_data(source, Path::new("test.rrm")).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Checks that parse -> write -> parse gives the same map and that writing is stable.
+fn assert_round_trip(map: &Map) {
+    let written = write_map_data(map).unwrap_or_else(|e| panic!("{}", e));
+    let reparsed = parse(&written);
+
+    assert_eq!(&reparsed, map, "round trip changed the map:\n{}", written);
+    assert_eq!(write_map_data(&reparsed).ok(), Some(written));
+}
+
+/// Checks that writing the map fails instead of writing text that loads as a different map.
+fn assert_unwritable(map: &Map) {
+    match write_map_data(map) {
+        Err(RrmError::Unwritable { .. }) => {}
+        other => panic!("expected an unwritable map, got {:?}", other),
+    }
+}
+
+fn tile(id: u8, name: &str) -> Tile {
+    Tile {
+        id,
+        name: name.to_string(),
+        solid: true,
+        wall: WallAppearance::Color(Color::RGB(128, 128, 128)),
+        map_color: Color::RGB(128, 128, 128),
+        floor: None,
+    }
+}
+
+#[test]
+fn readme_sample_round_trips() {
+    let map = parse(&readme_sample());
+
+    assert_eq!(map.width(), 8);
+    assert_eq!(map.height(), 8);
+    assert_eq!(map.metadata().name.as_deref(), Some("Cellar"));
+    assert!(map.tile_type(2).is_some_and(|tile| tile.solid));
+    assert_round_trip(&map);
+}
+
+#[test]
+fn default_map_round_trips() {
+    assert_round_trip(&Map::default());
+}
+
+#[test]
+fn all_headers_round_trip() {
+    let map = parse(
+        "NAME=Everything\n\
+         AUTHOR=Level Design\n\
+         SIZE=4x3\n\
+         CUBE_SIZE=32\n\
+         PLAYER_START=1.5,1.25,-90.5\n\
+         CEILING_COLOR=#101820\n\
+         FLOOR_COLOR=#40404080\n\
+         FOG=#101010,2,8.5\n\
+         TILE=0,void,passable,#000000,#00000000\n\
+         TILE=2,stone,solid,textures/stone.bmp,#808080\n\
+         TILE=3,rug,passable,#800000,#80000080,textures/rug.bmp\n\
+         TILE=4,tiles,passable,#000000,#00000000,#202020\n\
+         MUSIC=cellar.ogg\n\
+         WEATHER=rain\n\
+         [1, 1, 1, 1],\n\
+         [2, 4, 3, 1],\n\
+         [1, 2, 2, 1],\n",
+    );
+
+    let metadata = map.metadata();
+    assert_eq!(map.cube_size(), 32.0);
+    assert_eq!(
+        metadata.player_start,
+        Some(PlayerStart {
+            x: 1.5,
+            y: 1.25,
+            angle: -90.5
+        })
+    );
+    assert_eq!(
+        metadata.floor_color,
+        Some(Color::RGBA(0x40, 0x40, 0x40, 0x80))
+    );
+    assert_eq!(
+        metadata.fog,
+        Some(Fog {
+            color: Color::RGB(0x10, 0x10, 0x10),
+            start: 2.0,
+            end: 8.5
+        })
+    );
+    assert_eq!(metadata.get_extra("WEATHER"), Some("rain"));
+    assert_eq!(
+        map.tile_type(2).map(|tile| &tile.wall),
+        Some(&WallAppearance::Texture(String::from("textures/stone.bmp")))
+    );
+    assert_eq!(
+        map.tile_type(3).and_then(|tile| tile.floor.clone()),
+        Some(WallAppearance::Texture(String::from("textures/rug.bmp")))
+    );
+    assert_round_trip(&map);
+}
+
+#[test]
+fn edited_map_round_trips() {
+    let mut map = Map::new(3, 2, vec![1, 1, 1, 1, 0, 1]);
+    let mut palette = map.palette().clone();
+    palette.insert(Tile {
+        id: 7,
+        name: String::from("glass"),
+        solid: true,
+        wall: WallAppearance::Color(Color::RGBA(150, 200, 255, 128)),
+        map_color: Color::RGB(150, 200, 255),
+        floor: None,
+    });
+    map.set_palette(palette);
+    map.set_tile(1, 0, 7);
+    map.metadata_mut().name = Some(String::from("Generated"));
+
+    assert_round_trip(&map);
+}
+
+#[test]
+fn comments_are_not_written() {
+    let map = parse("# comment\nSIZE=1\n\n[1], # wall\n");
+
+    assert_eq!(
+        write_map_data(&map).ok().as_deref(),
+        Some("SIZE=1\n\n[1],\n")
+    );
+}
+
+#[test]
+fn player_start_cell_round_trips() {
+    let map = parse("SIZE=3\n[1, 1, 1],\n[1, P, 1],\n[1, 1, 1],\n");
+
+    assert_eq!(map.tile(1, 1), Some(0));
+    assert_eq!(
+        map.metadata().player_start,
+        Some(PlayerStart {
+            x: 1.5,
+            y: 1.5,
+            angle: 0.0
+        })
+    );
+    assert_eq!(
+        map.player_start(),
+        Some((1.5 * map.cube_size(), 1.5 * map.cube_size(), 0.0))
+    );
+    assert_round_trip(&map);
+}
+
+#[test]
+fn player_start_cell_needs_a_passable_tile_0() {
+    // Without a tile 0 and with a solid tile 0
+    for (source, expected_line) in [
+        (
+            "SIZE=3\nPALETTE=empty\nTILE=1,wall,solid,#00FF00,#FFFFFF\n[1, 1, 1],\n[1, P, 1],\n[1, 1, 1],\n",
+            5,
+        ),
+        (
+            "SIZE=3\nTILE=0,rock,solid,#808080,#808080\n[1, 1, 1],\n[1, P, 1],\n[1, 1, 1],\n",
+            4,
+        ),
+    ] {
+        match parse_map_data(source, Path::new("test.rrm")) {
+            Err(RrmError::BlockedPlayerStart { line, column, .. }) => {
+                assert_eq!((line, column), (expected_line, 5))
+            }
+            other => panic!("expected a blocked player start, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn player_start_header_must_be_finite() {
+    for value in ["NaN,1.5,0", "1.5,inf,0", "1.5,1.5,-inf"] {
+        let source = format!("SIZE=1\nPLAYER_START={}\n[1],\n", value);
+        assert!(
+            parse_map_data(&source, Path::new("test.rrm")).is_err(),
+            "{}",
+            value
+        );
+    }
+}
+
+#[test]
+fn entity_layer_round_trips() {
+    let map = parse(
+        "SIZE=3\n\
+         ENTITY=guard_1,hostile,1.5,1.5,90,0.75,20,1\n\
+         ENTITY=cat,neutral,1.25,1.75,0,0.25,5,2\n\
+         [1, 1, 1],\n\
+         [1, 0, 1],\n\
+         [1, 1, 1],\n",
+    );
+
+    assert_eq!(map.entity_spawns().len(), 2);
+    assert_eq!(map.entity_spawns()[0].kind, EntityKind::Hostile);
+    assert_eq!(map.entity_spawns()[1].sprite_id, 2);
+    assert_round_trip(&map);
+}
+
+#[test]
+fn palette_without_default_tiles_round_trips() {
+    let mut palette = Palette::new();
+    palette.insert(tile(2, "brick"));
+    palette.insert(tile(3, "stone"));
+    let map = Map::with_palette(2, 1, vec![2, 3], palette);
+
+    let written = write_map_data(&map).unwrap_or_else(|e| panic!("{}", e));
+    assert!(written.contains("PALETTE=empty\n"), "{}", written);
+    assert!(!parse(&written).palette().contains(0));
+    assert_round_trip(&map);
+}
+
+#[test]
+fn palette_header_must_come_before_tiles() {
+    let source = "SIZE=1\nTILE=2,brick,solid,#8B4513,#A0522D\nPALETTE=empty\n[2],\n";
+
+    assert!(parse_map_data(source, Path::new("test.rrm")).is_err());
+}
+
+#[test]
+fn tiles_missing_from_the_palette_are_not_written() {
+    assert_unwritable(&Map::new(2, 1, vec![1, 9]));
+}
+
+#[test]
+fn text_the_loader_would_split_is_not_written() {
+    let mut palette = Palette::default();
+    palette.insert(tile(2, "a,b"));
+    assert_unwritable(&Map::with_palette(1, 1, vec![2], palette));
+
+    let mut palette = Palette::default();
+    let mut textured = tile(2, "brick");
+    textured.wall = WallAppearance::Texture(String::from("#brick.bmp"));
+    palette.insert(textured);
+    assert_unwritable(&Map::with_palette(1, 1, vec![2], palette));
+
+    let mut map = Map::new(1, 1, vec![1]);
+    map.metadata_mut().name = Some(String::from("a\nSIZE=9"));
+    assert_unwritable(&map);
+
+    for (key, value) in [("a=b", "c"), ("SIZE", "9"), ("MUSIC", " cellar.ogg")] {
+        let mut map = Map::new(1, 1, vec![1]);
+        map.metadata_mut()
+            .extra
+            .push((key.to_string(), value.to_string()));
+        assert_unwritable(&map);
+    }
+
+    // Values may hold `=` and `,` where the loader doesn't split them
+    let mut map = Map::new(1, 1, vec![1]);
+    map.metadata_mut().name = Some(String::from("a=b, c"));
+    map.metadata_mut()
+        .extra
+        .push((String::from("MUSIC"), String::from("a=b,c")));
+    assert_round_trip(&map);
+}
+
+#[test]
+fn numbers_the_loader_rejects_are_not_written() {
+    for (start, end) in [
+        (-1.0, 8.0),
+        (8.0, 8.0),
+        (2.0, f32::INFINITY),
+        (f32::NAN, 8.0),
+    ] {
+        let mut map = Map::new(1, 1, vec![1]);
+        map.metadata_mut().fog = Some(Fog {
+            color: Color::RGB(0, 0, 0),
+            start,
+            end,
+        });
+        assert_unwritable(&map);
+    }
+
+    for (x, angle) in [(f32::NAN, 0.0), (0.5, f32::INFINITY)] {
+        let mut map = Map::new(1, 1, vec![1]);
+        map.metadata_mut().player_start = Some(PlayerStart { x, y: 0.5, angle });
+        assert_unwritable(&map);
+
+        let mut map = Map::new(1, 1, vec![1]);
+        map.entity_spawns_mut().push(EntitySpawn {
+            name: String::from("guard"),
+            kind: EntityKind::Hostile,
+            x,
+            y: 0.5,
+            angle,
+            speed: 0.5,
+            health: 20.0,
+            sprite_id: 0,
+        });
+        assert_unwritable(&map);
+    }
+}
@@ -0,0 +1,60 @@
+use rust_ray::entities::LevelEntities;
+use rust_ray::rendering::{Image, Sprite, SpriteSheet};
+use rust_ray::rrm::parse_map_data;
+use sdl2::pixels::Color;
+use std::path::Path;
+
+/// A 3x2 sheet of 2x2 sprites, where every sprite is filled with the colour (id, 0, 0).
+fn sheet() -> SpriteSheet {
+    let mut pixels = Vec::new();
+    for y in 0..4 {
+        for x in 0..6 {
+            let id = (y / 2) * 3 + x / 2;
+            pixels.extend_from_slice(&[id as u8, 0, 0, 255]);
+        }
+    }
+    SpriteSheet::new(Image::new(6, 4, pixels), 2, 2)
+}
+
+#[test]
+fn sprite_ids_count_cells_row_by_row() {
+    let sheet = sheet();
+
+    assert_eq!(sheet.columns(), 3);
+    assert_eq!(sheet.len(), 6);
+    assert_eq!(
+        sheet.texture_rect(4),
+        Some((1.0 / 3.0, 0.5, 2.0 / 3.0, 1.0))
+    );
+    assert_eq!(sheet.texture_rect(6), None);
+
+    for id in 0..6 {
+        assert_eq!(sheet.sample(id, 0.0, 0.0), Some(Color::RGB(id as u8, 0, 0)));
+        assert_eq!(sheet.sample(id, 1.0, 1.0), Some(Color::RGB(id as u8, 0, 0)));
+    }
+}
+
+#[test]
+fn living_entities_become_sprites() {
+    let map = parse_map_data(
+        "SIZE=3\n\
+         ENTITY=guard,hostile,1.5,1.5,0,0.5,20,4\n\
+         ENTITY=cat,neutral,1.25,1.75,0,0.25,5,2\n\
+         [1, 1, 1],\n\
+         [1, 0, 1],\n\
+         [1, 1, 1],\n",
+        Path::new("test.rrm"),
+    )
+    .unwrap();
+    let mut level_entities = LevelEntities::from_map(&map);
+    level_entities.neutral[0].1.is_alive = false;
+
+    assert_eq!(
+        level_entities.sprites(),
+        vec![Sprite {
+            x: 1.5 * map.cube_size(),
+            y: 1.5 * map.cube_size(),
+            sprite_id: 4
+        }]
+    );
+}